@@ -0,0 +1,168 @@
+//! Measures how long it takes to draw a GUI with 200 knobs when
+//! everything changes every frame, when one knob changes per frame,
+//! and when nothing changes.
+//!
+//! Frames are drawn the way the ggez backend draws them, by updating
+//! the draw cache and reading the cached operations without copying them.
+//!
+//! Run with `cargo bench -p knobby`.
+
+use knobby::{
+	behavior::Behavior,
+	canvas::{Alignment, ArcKind, Canvas, Color, DrawMode, Paint, Stroke, Style, TextStyle},
+	control::{Control, ControlSettings},
+	event::Event,
+	geometry::Rectangle,
+	gui::{ControlId, Controls, EventQueue, Gui, Resources},
+	meter::Meters,
+	parameter::Parameters,
+};
+use std::{
+	f32::consts::PI,
+	time::{Duration, Instant},
+};
+
+const COLUMNS: usize = 20;
+const ROWS: usize = 10;
+const KNOB_SIZE: f32 = 40.0;
+const FRAMES: u32 = 1000;
+
+struct Knob {
+	id: Option<ControlId>,
+	value: f32,
+}
+
+impl Behavior<usize> for Knob {
+	fn on(
+		&mut self,
+		event: Event<usize>,
+		controls: &mut Controls,
+		_resources: &Resources,
+		_parameters: &Parameters,
+		_event_queue: &mut EventQueue<usize>,
+	) {
+		match event {
+			Event::Added(id) => self.id = Some(id),
+			Event::Custom(_) => {
+				if let Some(id) = self.id {
+					self.value = (self.value + 0.01) % 1.0;
+					controls.request_redraw(&id);
+				}
+			}
+			_ => {}
+		}
+	}
+
+	fn draw(
+		&self,
+		control: &Control,
+		_resources: &Resources,
+		_parameters: &Parameters,
+		_meters: &Meters,
+		canvas: &mut Canvas,
+	) {
+		let center = control.absolute_rectangle.get_center();
+		let radius = control.absolute_rectangle.height / 2.0;
+		let style = Style {
+			mode: DrawMode::Stroke(Stroke::new(2.0)),
+			paint: Paint::Solid(Color::new(1.0, 1.0, 1.0, 1.0)),
+		};
+		canvas.draw_circle(center, radius, style.clone());
+		let angle = 0.75 * PI + self.value * 1.5 * PI;
+		canvas.draw_arc(
+			ArcKind::Open,
+			center,
+			radius * 0.75,
+			0.75 * PI,
+			angle,
+			style.clone(),
+		);
+		let ticks = (0..=10)
+			.map(|i| {
+				let angle = 0.75 * PI + i as f32 / 10.0 * 1.5 * PI;
+				center.shifted(angle.cos() * radius, angle.sin() * radius)
+			})
+			.collect();
+		canvas.draw_polyline(ticks, style);
+		canvas.draw_text(
+			format!("{:.2}", self.value),
+			center,
+			TextStyle {
+				font_id: 0,
+				size: 12.0,
+				horizontal_alignment: Alignment::Middle,
+				vertical_alignment: Alignment::Middle,
+				color: Color::new(1.0, 1.0, 1.0, 1.0),
+			},
+		);
+	}
+}
+
+fn create_gui() -> (Gui<usize>, Vec<ControlId>) {
+	let mut gui = Gui::new();
+	let mut ids = vec![];
+	for row in 0..ROWS {
+		for column in 0..COLUMNS {
+			ids.push(
+				gui.add_control(
+					ControlSettings {
+						rectangle: Rectangle::new(
+							column as f32 * KNOB_SIZE,
+							row as f32 * KNOB_SIZE,
+							KNOB_SIZE,
+							KNOB_SIZE,
+						),
+						height: 0,
+						parent: None,
+						visible: true,
+						focusable: false,
+					},
+					vec![Box::new(Knob {
+						id: None,
+						value: 0.0,
+					})],
+				)
+				.unwrap(),
+			);
+		}
+	}
+	(gui, ids)
+}
+
+/// Draws `FRAMES` frames, calling `change` before each one,
+/// and returns the average time per frame.
+fn measure(mut change: impl FnMut(&mut Gui<usize>, u32)) -> Duration {
+	let (mut gui, _) = create_gui();
+	let mut operation_count = 0;
+	let start = Instant::now();
+	for frame in 0..FRAMES {
+		change(&mut gui, frame);
+		gui.update_draw_cache();
+		operation_count += gui.draw_operations().count();
+	}
+	let elapsed = start.elapsed();
+	assert_eq!(operation_count, FRAMES as usize * COLUMNS * ROWS * 4);
+	elapsed / FRAMES
+}
+
+fn main() {
+	let (_, ids) = create_gui();
+	let results = [
+		(
+			"everything changes",
+			measure(|gui, _| gui.request_full_redraw()),
+		),
+		(
+			"one knob changes",
+			measure(|gui, frame| {
+				let id = ids[frame as usize % ids.len()];
+				gui.emit(Event::Custom(0), Some(id));
+			}),
+		),
+		("nothing changes", measure(|_, _| {})),
+	];
+	println!("drawing {} knobs ({} frames each):", COLUMNS * ROWS, FRAMES);
+	for (name, time) in &results {
+		println!("  {:<20} {:>10.2?} per frame", name, time);
+	}
+}
@@ -0,0 +1,399 @@
+use crate::{
+	canvas::Color,
+	geometry::{Point, Rectangle},
+};
+use std::{any::Any, f32::consts::PI};
+
+/// A value that can be smoothly animated.
+pub trait Animatable: Copy {
+	/// Returns the value `amount` of the way (from 0-1)
+	/// between this value and another.
+	fn lerp(self, other: Self, amount: f32) -> Self;
+
+	/// Adds two values together.
+	fn add(self, other: Self) -> Self;
+
+	/// Multiplies each component of the value by the given amount.
+	fn scale(self, amount: f32) -> Self;
+
+	/// Returns the largest absolute component of the value.
+	fn magnitude(self) -> f32;
+}
+
+impl Animatable for f32 {
+	fn lerp(self, other: Self, amount: f32) -> Self {
+		self + (other - self) * amount
+	}
+
+	fn add(self, other: Self) -> Self {
+		self + other
+	}
+
+	fn scale(self, amount: f32) -> Self {
+		self * amount
+	}
+
+	fn magnitude(self) -> f32 {
+		self.abs()
+	}
+}
+
+impl Animatable for Color {
+	fn lerp(self, other: Self, amount: f32) -> Self {
+		Color::new(
+			self.red.lerp(other.red, amount),
+			self.green.lerp(other.green, amount),
+			self.blue.lerp(other.blue, amount),
+			self.alpha.lerp(other.alpha, amount),
+		)
+	}
+
+	fn add(self, other: Self) -> Self {
+		Color::new(
+			self.red + other.red,
+			self.green + other.green,
+			self.blue + other.blue,
+			self.alpha + other.alpha,
+		)
+	}
+
+	fn scale(self, amount: f32) -> Self {
+		Color::new(
+			self.red * amount,
+			self.green * amount,
+			self.blue * amount,
+			self.alpha * amount,
+		)
+	}
+
+	fn magnitude(self) -> f32 {
+		self.red
+			.abs()
+			.max(self.green.abs())
+			.max(self.blue.abs())
+			.max(self.alpha.abs())
+	}
+}
+
+impl Animatable for Point {
+	fn lerp(self, other: Self, amount: f32) -> Self {
+		Point::new(self.x.lerp(other.x, amount), self.y.lerp(other.y, amount))
+	}
+
+	fn add(self, other: Self) -> Self {
+		Point::new(self.x + other.x, self.y + other.y)
+	}
+
+	fn scale(self, amount: f32) -> Self {
+		Point::new(self.x * amount, self.y * amount)
+	}
+
+	fn magnitude(self) -> f32 {
+		self.x.abs().max(self.y.abs())
+	}
+}
+
+impl Animatable for Rectangle {
+	fn lerp(self, other: Self, amount: f32) -> Self {
+		Rectangle::new(
+			self.x.lerp(other.x, amount),
+			self.y.lerp(other.y, amount),
+			self.width.lerp(other.width, amount),
+			self.height.lerp(other.height, amount),
+		)
+	}
+
+	fn add(self, other: Self) -> Self {
+		Rectangle::new(
+			self.x + other.x,
+			self.y + other.y,
+			self.width + other.width,
+			self.height + other.height,
+		)
+	}
+
+	fn scale(self, amount: f32) -> Self {
+		Rectangle::new(
+			self.x * amount,
+			self.y * amount,
+			self.width * amount,
+			self.height * amount,
+		)
+	}
+
+	fn magnitude(self) -> f32 {
+		self.x
+			.abs()
+			.max(self.y.abs())
+			.max(self.width.abs())
+			.max(self.height.abs())
+	}
+}
+
+/// A curve that controls how a tween moves from its start
+/// value to its end value.
+#[derive(Copy, Clone, Debug)]
+pub enum Easing {
+	/// Moves at a constant speed.
+	Linear,
+	/// Starts slow and speeds up.
+	QuadIn,
+	/// Starts fast and slows down.
+	QuadOut,
+	/// Starts slow, speeds up, and slows down again.
+	QuadInOut,
+	/// Like `QuadIn`, but more pronounced.
+	CubicIn,
+	/// Like `QuadOut`, but more pronounced.
+	CubicOut,
+	/// Like `QuadInOut`, but more pronounced.
+	CubicInOut,
+	/// Starts and ends gently, following a sine wave.
+	SineInOut,
+	/// Overshoots the end value slightly before settling.
+	BackOut,
+}
+
+impl Easing {
+	/// Applies the easing curve to an amount of progress (from 0-1).
+	pub fn apply(&self, t: f32) -> f32 {
+		let t = t.clamp(0.0, 1.0);
+		match self {
+			Easing::Linear => t,
+			Easing::QuadIn => t * t,
+			Easing::QuadOut => 1.0 - (1.0 - t) * (1.0 - t),
+			Easing::QuadInOut => {
+				if t < 0.5 {
+					2.0 * t * t
+				} else {
+					1.0 - (-2.0 * t + 2.0).powi(2) / 2.0
+				}
+			}
+			Easing::CubicIn => t * t * t,
+			Easing::CubicOut => 1.0 - (1.0 - t).powi(3),
+			Easing::CubicInOut => {
+				if t < 0.5 {
+					4.0 * t * t * t
+				} else {
+					1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+				}
+			}
+			Easing::SineInOut => -((PI * t).cos() - 1.0) / 2.0,
+			Easing::BackOut => {
+				let c1 = 1.70158;
+				let c3 = c1 + 1.0;
+				1.0 + c3 * (t - 1.0).powi(3) + c1 * (t - 1.0).powi(2)
+			}
+		}
+	}
+}
+
+/// Something that changes over time.
+pub trait Animation {
+	/// Advances the animation by `dt` seconds.
+	fn update(&mut self, dt: f32);
+
+	/// Returns `true` if the animation has come to rest.
+	fn is_finished(&self) -> bool;
+}
+
+/// An animation of a value, which the GUI can run on a control's
+/// behalf (see `Controls::start_animation`).
+pub trait ValueAnimation<T>: Animation {
+	/// Gets the current value.
+	fn value(&self) -> T;
+}
+
+/// A `ValueAnimation` of any type, so animations of different types
+/// can be stored together and read back as the type they animate.
+pub(crate) trait AnyValueAnimation: Animation {
+	fn as_any(&self) -> &dyn Any;
+}
+
+impl dyn AnyValueAnimation {
+	/// Gets the current value, or `None` if the animation
+	/// doesn't animate values of type `T`.
+	pub(crate) fn value<T: 'static>(&self) -> Option<T> {
+		self.as_any()
+			.downcast_ref::<BoxedValueAnimation<T>>()
+			.map(|animation| animation.0.value())
+	}
+}
+
+pub(crate) struct BoxedValueAnimation<T>(pub(crate) Box<dyn ValueAnimation<T>>);
+
+impl<T> Animation for BoxedValueAnimation<T> {
+	fn update(&mut self, dt: f32) {
+		self.0.update(dt);
+	}
+
+	fn is_finished(&self) -> bool {
+		self.0.is_finished()
+	}
+}
+
+impl<T: 'static> AnyValueAnimation for BoxedValueAnimation<T> {
+	fn as_any(&self) -> &dyn Any {
+		self
+	}
+}
+
+/// Smoothly moves a value to a target over a fixed amount of time.
+#[derive(Copy, Clone, Debug)]
+pub struct Tween<T: Animatable> {
+	start: T,
+	end: T,
+	duration: f32,
+	elapsed: f32,
+	easing: Easing,
+}
+
+impl<T: Animatable> Tween<T> {
+	/// Creates a new tween that's resting at the given value.
+	pub fn new(value: T) -> Self {
+		Self {
+			start: value,
+			end: value,
+			duration: 0.0,
+			elapsed: 0.0,
+			easing: Easing::Linear,
+		}
+	}
+
+	/// Starts moving from the current value to a new value.
+	pub fn animate_to(&mut self, target: T, duration: f32, easing: Easing) {
+		self.start = self.value();
+		self.end = target;
+		self.duration = duration;
+		self.elapsed = 0.0;
+		self.easing = easing;
+	}
+
+	/// Immediately jumps to a value, stopping any animation.
+	pub fn set(&mut self, value: T) {
+		*self = Self::new(value);
+	}
+
+	/// Gets the current value.
+	pub fn value(&self) -> T {
+		if self.duration <= 0.0 {
+			return self.end;
+		}
+		let progress = self.easing.apply(self.elapsed / self.duration);
+		self.start.lerp(self.end, progress)
+	}
+
+	/// Gets the value the tween is moving towards.
+	pub fn target(&self) -> T {
+		self.end
+	}
+}
+
+impl<T: Animatable> Animation for Tween<T> {
+	fn update(&mut self, dt: f32) {
+		self.elapsed = (self.elapsed + dt).min(self.duration);
+	}
+
+	fn is_finished(&self) -> bool {
+		self.elapsed >= self.duration
+	}
+}
+
+impl<T: Animatable> ValueAnimation<T> for Tween<T> {
+	fn value(&self) -> T {
+		Tween::value(self)
+	}
+}
+
+/// Moves a value towards a target using spring physics.
+///
+/// Unlike a `Tween`, a spring keeps its velocity when the target
+/// changes, so it's a good fit for values that change often,
+/// like a knob that follows a parameter.
+#[derive(Copy, Clone, Debug)]
+pub struct Spring<T: Animatable> {
+	/// How strongly the spring pulls the value towards the target.
+	pub stiffness: f32,
+	/// How much the spring resists motion. Higher values mean
+	/// less bouncing.
+	pub damping: f32,
+	value: T,
+	velocity: T,
+	target: T,
+}
+
+impl<T: Animatable> Spring<T> {
+	/// The distance from the target (and speed) below which
+	/// the spring is considered to be at rest.
+	const REST_THRESHOLD: f32 = 0.001;
+
+	/// The longest amount of time (in seconds) the spring
+	/// simulation will advance in a single step.
+	const MAX_STEP: f32 = 1.0 / 240.0;
+
+	/// Creates a new spring that's resting at the given value.
+	pub fn new(value: T, stiffness: f32, damping: f32) -> Self {
+		Self {
+			stiffness,
+			damping,
+			value,
+			velocity: value.scale(0.0),
+			target: value,
+		}
+	}
+
+	/// Sets the value the spring should move towards.
+	pub fn animate_to(&mut self, target: T) {
+		self.target = target;
+	}
+
+	/// Immediately jumps to a value, stopping any motion.
+	pub fn set(&mut self, value: T) {
+		self.value = value;
+		self.target = value;
+		self.velocity = value.scale(0.0);
+	}
+
+	/// Gets the current value.
+	pub fn value(&self) -> T {
+		self.value
+	}
+
+	/// Gets the value the spring is moving towards.
+	pub fn target(&self) -> T {
+		self.target
+	}
+}
+
+impl<T: Animatable> Animation for Spring<T> {
+	fn update(&mut self, dt: f32) {
+		if self.is_finished() {
+			self.set(self.target);
+			return;
+		}
+		// split long frames into smaller steps to keep the
+		// simulation stable
+		let steps = (dt / Self::MAX_STEP).ceil().max(1.0);
+		let step = dt / steps;
+		for _ in 0..steps as usize {
+			// semi-implicit euler integration
+			let displacement = self.target.add(self.value.scale(-1.0));
+			let acceleration = displacement
+				.scale(self.stiffness)
+				.add(self.velocity.scale(-self.damping));
+			self.velocity = self.velocity.add(acceleration.scale(step));
+			self.value = self.value.add(self.velocity.scale(step));
+		}
+	}
+
+	fn is_finished(&self) -> bool {
+		self.target.add(self.value.scale(-1.0)).magnitude() < Self::REST_THRESHOLD
+			&& self.velocity.magnitude() < Self::REST_THRESHOLD
+	}
+}
+
+impl<T: Animatable> ValueAnimation<T> for Spring<T> {
+	fn value(&self) -> T {
+		Spring::value(self)
+	}
+}
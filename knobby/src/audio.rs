@@ -0,0 +1,484 @@
+use crate::event::Event;
+use std::{
+	cell::UnsafeCell,
+	collections::VecDeque,
+	mem::MaybeUninit,
+	sync::{
+		atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering},
+		Arc,
+	},
+};
+
+/// What to do when an event is sent to a queue that's full.
+#[derive(Copy, Clone, Debug)]
+pub enum OverflowPolicy {
+	/// The new event is dropped.
+	DropNewest,
+	/// The oldest event in the queue is overwritten by the new event.
+	///
+	/// Parameter gesture events are never overwritten. If the oldest
+	/// event is a gesture event, the new event is dropped instead.
+	DropOldest,
+	/// `SetParameter` events for parameters with indices from 0 up to
+	/// (but not including) the given count don't use the queue at all.
+	/// Instead, only the most recent value of each parameter is kept
+	/// until it's received, so parameter changes are never dropped.
+	///
+	/// Other events are dropped when the queue is full.
+	CoalesceParameters(usize),
+}
+
+/// Settings for the queues between the GUI and the audio thread.
+#[derive(Copy, Clone, Debug)]
+pub struct AudioBridgeSettings {
+	/// The maximum number of events each queue can hold.
+	///
+	/// This is rounded up to the nearest power of two.
+	pub capacity: usize,
+	/// What to do when the queue from the GUI to the audio
+	/// thread is full.
+	pub gui_to_audio_policy: OverflowPolicy,
+	/// What to do when the queue from the audio thread to the GUI
+	/// is full.
+	pub audio_to_gui_policy: OverflowPolicy,
+}
+
+impl Default for AudioBridgeSettings {
+	fn default() -> Self {
+		Self {
+			capacity: 1024,
+			gui_to_audio_policy: OverflowPolicy::DropOldest,
+			audio_to_gui_policy: OverflowPolicy::DropOldest,
+		}
+	}
+}
+
+/// A bounded single-producer, single-consumer ring buffer.
+///
+/// Each slot has a sequence number that says whether it's waiting to be
+/// written or read, so neither side ever has to wait for the other, and
+/// a slot is only ever written by the side that owns it.
+///
+/// If the queue has an `overwrite` function, pushing to a full queue
+/// removes the oldest value instead of failing, as long as the function
+/// returns `true` for it. The sender claims the oldest value the same way
+/// the receiver does, so if the receiver is reading it at that moment,
+/// the new value is rejected instead.
+struct Queue<T> {
+	slots: Box<[Slot<T>]>,
+	mask: usize,
+	overwrite: Option<fn(&T) -> bool>,
+	/// The position of the next value to pop. The sender only changes
+	/// this when it removes the oldest value from a full queue.
+	head: AtomicUsize,
+	/// The position the next value will be pushed to. Only the sender
+	/// changes this.
+	tail: AtomicUsize,
+}
+
+struct Slot<T> {
+	/// `position` if the slot is waiting for the value at `position`
+	/// to be pushed, or `position + 1` once that value can be popped.
+	sequence: AtomicUsize,
+	value: UnsafeCell<MaybeUninit<T>>,
+}
+
+unsafe impl<T: Send> Send for Queue<T> {}
+unsafe impl<T: Send> Sync for Queue<T> {}
+
+impl<T: Copy> Queue<T> {
+	fn new(capacity: usize, overwrite: Option<fn(&T) -> bool>) -> Self {
+		let capacity = capacity.max(2).next_power_of_two();
+		let slots = (0..capacity)
+			.map(|position| Slot {
+				sequence: AtomicUsize::new(position),
+				value: UnsafeCell::new(MaybeUninit::uninit()),
+			})
+			.collect();
+		Self {
+			slots,
+			mask: capacity - 1,
+			overwrite,
+			head: AtomicUsize::new(0),
+			tail: AtomicUsize::new(0),
+		}
+	}
+
+	fn capacity(&self) -> usize {
+		self.mask + 1
+	}
+
+	/// Pushes a value to the queue. Must only be called from the sending thread.
+	fn push(&self, value: T) -> Result<(), T> {
+		let tail = self.tail.load(Ordering::Relaxed);
+		let slot = &self.slots[tail & self.mask];
+		if slot.sequence.load(Ordering::Acquire) != tail {
+			// the slot still holds the oldest value, which the receiver
+			// either hasn't popped or is popping right now
+			let overwrite = match self.overwrite {
+				Some(overwrite) => overwrite,
+				None => return Err(value),
+			};
+			// the receiver only ever reads the slot, so it's fine for
+			// both sides to look at the value at once
+			if !overwrite(unsafe { &*(*slot.value.get()).as_ptr() }) {
+				return Err(value);
+			}
+			let oldest = tail.wrapping_sub(self.capacity());
+			if self
+				.head
+				.compare_exchange(
+					oldest,
+					oldest.wrapping_add(1),
+					Ordering::AcqRel,
+					Ordering::Relaxed,
+				)
+				.is_err()
+			{
+				return Err(value);
+			}
+		}
+		unsafe { (*slot.value.get()).as_mut_ptr().write(value) };
+		slot.sequence.store(tail.wrapping_add(1), Ordering::Release);
+		self.tail.store(tail.wrapping_add(1), Ordering::Relaxed);
+		Ok(())
+	}
+
+	/// Pops the oldest value from the queue. Must only be called
+	/// from the receiving thread.
+	fn pop(&self) -> Option<T> {
+		loop {
+			let head = self.head.load(Ordering::Acquire);
+			let slot = &self.slots[head & self.mask];
+			if slot.sequence.load(Ordering::Acquire) != head.wrapping_add(1) {
+				if self.head.load(Ordering::Acquire) == head {
+					return None;
+				}
+				// the sender removed the oldest value while we were
+				// looking at it
+				continue;
+			}
+			// claim the value before reading it, so the sender can't
+			// overwrite it while it's being read
+			if self
+				.head
+				.compare_exchange(
+					head,
+					head.wrapping_add(1),
+					Ordering::AcqRel,
+					Ordering::Relaxed,
+				)
+				.is_err()
+			{
+				continue;
+			}
+			let value = unsafe { (*slot.value.get()).as_ptr().read() };
+			slot.sequence
+				.store(head.wrapping_add(self.capacity()), Ordering::Release);
+			return Some(value);
+		}
+	}
+}
+
+/// Holds the most recent value of each parameter until
+/// it's received.
+struct ParameterSlots {
+	values: Box<[AtomicU32]>,
+	changed: Box<[AtomicBool]>,
+}
+
+impl ParameterSlots {
+	fn new(count: usize) -> Self {
+		Self {
+			values: (0..count).map(|_| AtomicU32::new(0)).collect(),
+			changed: (0..count).map(|_| AtomicBool::new(false)).collect(),
+		}
+	}
+
+	fn set(&self, index: usize, value: f32) {
+		self.values[index].store(value.to_bits(), Ordering::Relaxed);
+		self.changed[index].store(true, Ordering::Release);
+	}
+
+	fn take(&self, index: usize) -> Option<f32> {
+		if self.changed[index].swap(false, Ordering::Acquire) {
+			Some(f32::from_bits(self.values[index].load(Ordering::Relaxed)))
+		} else {
+			None
+		}
+	}
+}
+
+struct Channel<CustomEvent> {
+	queue: Queue<Event<CustomEvent>>,
+	parameters: ParameterSlots,
+}
+
+impl<CustomEvent: Copy> Channel<CustomEvent> {
+	fn new(capacity: usize, policy: OverflowPolicy) -> Self {
+		let parameter_count = match policy {
+			OverflowPolicy::CoalesceParameters(count) => count,
+			_ => 0,
+		};
+		let overwrite = match policy {
+			OverflowPolicy::DropOldest => {
+				Some(can_drop::<CustomEvent> as fn(&Event<CustomEvent>) -> bool)
+			}
+			_ => None,
+		};
+		Self {
+			queue: Queue::new(capacity, overwrite),
+			parameters: ParameterSlots::new(parameter_count),
+		}
+	}
+
+	fn send(&self, event: Event<CustomEvent>) -> bool {
+		if let Event::SetParameter(index, value) = event {
+			if index >= 0 && (index as usize) < self.parameters.values.len() {
+				self.parameters.set(index as usize, value);
+				return true;
+			}
+		}
+		// parameter changes that haven't been received yet have to
+		// arrive before this event, so move them into the queue first
+		if !self.flush_parameters() {
+			return false;
+		}
+		self.queue.push(event).is_ok()
+	}
+
+	/// Moves the coalesced parameter changes into the queue. Returns
+	/// `false` if the queue filled up before every change was moved.
+	fn flush_parameters(&self) -> bool {
+		for index in 0..self.parameters.values.len() {
+			if let Some(value) = self.parameters.take(index) {
+				if self
+					.queue
+					.push(Event::SetParameter(index as i32, value))
+					.is_err()
+				{
+					// keep the change in its slot so it isn't lost
+					self.parameters.set(index, value);
+					return false;
+				}
+			}
+		}
+		true
+	}
+}
+
+/// Returns `false` for events that have to arrive, like parameter
+/// gesture events, which are always sent in pairs.
+fn can_drop<CustomEvent>(event: &Event<CustomEvent>) -> bool {
+	!matches!(
+		event,
+		Event::BeginParameterGesture(_) | Event::EndParameterGesture(_)
+	)
+}
+
+/// The sending end of a channel.
+pub(crate) struct Sender<CustomEvent> {
+	channel: Arc<Channel<CustomEvent>>,
+}
+
+impl<CustomEvent: Copy> Sender<CustomEvent> {
+	/// Sends an event. Returns `false` if the event was dropped.
+	pub(crate) fn send(&mut self, event: Event<CustomEvent>) -> bool {
+		self.channel.send(event)
+	}
+}
+
+/// The receiving end of a channel.
+pub(crate) struct Receiver<CustomEvent> {
+	channel: Arc<Channel<CustomEvent>>,
+	next_parameter: usize,
+}
+
+impl<CustomEvent: Copy> Receiver<CustomEvent> {
+	/// Receives the next event, if there is one.
+	///
+	/// Events in the queue are received first, followed by
+	/// any coalesced parameter changes. Parameter changes sent before
+	/// another kind of event are moved into the queue ahead of it,
+	/// so they're never received out of order.
+	pub(crate) fn receive(&mut self) -> Option<Event<CustomEvent>> {
+		if let Some(event) = self.channel.queue.pop() {
+			return Some(event);
+		}
+		let parameters = &self.channel.parameters;
+		while self.next_parameter < parameters.values.len() {
+			let index = self.next_parameter;
+			self.next_parameter += 1;
+			if let Some(value) = parameters.take(index) {
+				return Some(Event::SetParameter(index as i32, value));
+			}
+		}
+		self.next_parameter = 0;
+		None
+	}
+}
+
+fn channel<CustomEvent: Copy>(
+	capacity: usize,
+	policy: OverflowPolicy,
+) -> (Sender<CustomEvent>, Receiver<CustomEvent>) {
+	let channel = Arc::new(Channel::new(capacity, policy));
+	(
+		Sender {
+			channel: channel.clone(),
+		},
+		Receiver {
+			channel,
+			next_parameter: 0,
+		},
+	)
+}
+
+/// The GUI's end of the connection to the audio thread.
+pub(crate) struct GuiConnection<CustomEvent> {
+	to_audio: Sender<CustomEvent>,
+	pub(crate) from_audio: Receiver<CustomEvent>,
+	/// Events that didn't fit in the queue but have to arrive,
+	/// along with the events sent after them, in order.
+	unsent: VecDeque<Event<CustomEvent>>,
+	capacity: usize,
+}
+
+impl<CustomEvent: Copy> GuiConnection<CustomEvent> {
+	/// Sends an event to the audio thread.
+	///
+	/// Gesture events that don't fit in the queue are kept and sent
+	/// on a later call, so the audio thread always receives both halves
+	/// of a gesture. Other events sent while there are unsent gesture
+	/// events are kept too (up to the capacity of the queue), so nothing
+	/// arrives out of order.
+	pub(crate) fn send(&mut self, event: Event<CustomEvent>) {
+		self.send_unsent();
+		if self.unsent.is_empty() && self.to_audio.send(event) {
+			return;
+		}
+		if !can_drop(&event) || (!self.unsent.is_empty() && self.unsent.len() < self.capacity) {
+			self.unsent.push_back(event);
+		}
+	}
+
+	/// Tries again to send the events that didn't fit in the queue.
+	pub(crate) fn send_unsent(&mut self) {
+		while let Some(event) = self.unsent.front() {
+			if !self.to_audio.send(*event) {
+				break;
+			}
+			self.unsent.pop_front();
+		}
+	}
+}
+
+/// The audio thread's end of the connection to the GUI.
+///
+/// An `AudioHandle` is created by `Gui::connect_audio` and can be moved
+/// to the audio thread. Sending and receiving events through the handle
+/// never allocates memory, takes a lock, or waits for the GUI thread,
+/// so it's safe to use in a real-time audio callback.
+pub struct AudioHandle<CustomEvent> {
+	from_gui: Receiver<CustomEvent>,
+	to_gui: Sender<CustomEvent>,
+}
+
+impl<CustomEvent: Copy> AudioHandle<CustomEvent> {
+	/// Receives the next event sent from the GUI, if there is one.
+	pub fn receive(&mut self) -> Option<Event<CustomEvent>> {
+		self.from_gui.receive()
+	}
+
+	/// Sends an event to the GUI.
+	///
+	/// Returns `false` if the event was dropped because
+	/// the queue was full.
+	pub fn send(&mut self, event: Event<CustomEvent>) -> bool {
+		self.to_gui.send(event)
+	}
+}
+
+pub(crate) fn connect<CustomEvent: Copy>(
+	settings: AudioBridgeSettings,
+) -> (GuiConnection<CustomEvent>, AudioHandle<CustomEvent>) {
+	let (to_audio, from_gui) = channel(settings.capacity, settings.gui_to_audio_policy);
+	let (to_gui, from_audio) = channel(settings.capacity, settings.audio_to_gui_policy);
+	(
+		GuiConnection {
+			to_audio,
+			from_audio,
+			unsent: VecDeque::new(),
+			capacity: settings.capacity,
+		},
+		AudioHandle { from_gui, to_gui },
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::thread;
+
+	fn overwrite_anything(_: &[usize; 16]) -> bool {
+		true
+	}
+
+	/// Sends values from one thread to another while the queue is
+	/// constantly full, checking that every value arrives whole.
+	fn stress(overwrite: Option<fn(&[usize; 16]) -> bool>) {
+		const COUNT: usize = 200_000;
+		let queue = Arc::new(Queue::new(4, overwrite));
+		let receiver = {
+			let queue = queue.clone();
+			thread::spawn(move || {
+				let mut previous = None;
+				while previous != Some(COUNT - 1) {
+					if let Some(value) = queue.pop() {
+						// a value that was read while it was being written
+						// would be made of more than one number
+						assert!(value.iter().all(|number| *number == value[0]));
+						if let Some(previous) = previous {
+							assert!(value[0] > previous);
+						}
+						previous = Some(value[0]);
+					}
+				}
+			})
+		};
+		for i in 0..COUNT {
+			// keep trying until the receiver makes room, since the
+			// last value has to arrive for the receiver to finish
+			while queue.push([i; 16]).is_err() && i == COUNT - 1 {
+				thread::yield_now();
+			}
+		}
+		receiver.join().unwrap();
+	}
+
+	#[test]
+	fn values_arrive_whole_when_dropping_newest() {
+		stress(None);
+	}
+
+	#[test]
+	fn values_arrive_whole_when_dropping_oldest() {
+		stress(Some(overwrite_anything));
+	}
+
+	#[test]
+	fn protected_values_are_not_overwritten() {
+		let queue: Queue<usize> = Queue::new(2, Some(|value| *value != 0));
+		assert!(queue.push(0).is_ok());
+		assert!(queue.push(1).is_ok());
+		// the oldest value is protected, so the new one is rejected
+		assert_eq!(queue.push(2), Err(2));
+		assert_eq!(queue.pop(), Some(0));
+		assert!(queue.push(3).is_ok());
+		// now the oldest value can be overwritten
+		assert!(queue.push(4).is_ok());
+		assert_eq!(queue.pop(), Some(3));
+		assert_eq!(queue.pop(), Some(4));
+		assert_eq!(queue.pop(), None);
+	}
+}
@@ -3,6 +3,8 @@ use crate::{
 	control::Control,
 	event::Event,
 	gui::{Controls, EventQueue, Resources},
+	meter::Meters,
+	parameter::Parameters,
 };
 
 /// Defines a set of behaviors and a visual representation for
@@ -25,10 +27,38 @@ pub trait Behavior<CustomEvent> {
 		_event: Event<CustomEvent>,
 		_controls: &mut Controls,
 		_resources: &Resources,
+		_parameters: &Parameters,
 		_event_queue: &mut EventQueue<CustomEvent>,
 	) {
 	}
 
+	/// Whether the behavior should receive an `Update` event
+	/// every frame.
+	fn wants_updates(&self) -> bool {
+		false
+	}
+
+	/// Whether the behavior has an animation in progress.
+	///
+	/// Behaviors that are animating receive an `Update` event every
+	/// frame (even if they don't want updates otherwise), which they
+	/// can use to advance their animations.
+	fn is_animating(&self) -> bool {
+		false
+	}
+
 	/// Defines how a control should be drawn.
-	fn draw(&self, _control: &Control, _resources: &Resources, _canvas: &mut Canvas) {}
+	///
+	/// The drawing operations are reused on later frames until the
+	/// control is marked as dirty, so behaviors that keep their own
+	/// state should call `Controls::request_redraw` when it changes.
+	fn draw(
+		&self,
+		_control: &Control,
+		_resources: &Resources,
+		_parameters: &Parameters,
+		_meters: &Meters,
+		_canvas: &mut Canvas,
+	) {
+	}
 }
@@ -1,4 +1,11 @@
-use super::geometry::{Point, Rectangle};
+use super::{
+	animation::Animatable,
+	geometry::{Point, Rectangle, Transform},
+	gui::ImageId,
+	image::Filmstrip,
+	path::Path,
+	svg::Svg,
+};
 
 /// An RGBA color.
 #[derive(Copy, Clone)]
@@ -25,27 +32,211 @@ impl Color {
 	}
 }
 
-/// How to draw a shape.
+/// A point in a gradient where the gradient reaches a certain color.
 #[derive(Copy, Clone)]
+pub struct GradientStop {
+	/// How far along the gradient (from 0-1) the stop is.
+	pub offset: f32,
+	/// The color of the gradient at the stop.
+	pub color: Color,
+}
+
+impl GradientStop {
+	/// Creates a new gradient stop.
+	pub fn new(offset: f32, color: Color) -> Self {
+		Self { offset, color }
+	}
+}
+
+/// How a shape is colored.
+///
+/// The colors of gradients blend smoothly between each stop. Before the
+/// first stop and after the last stop, the color of the nearest stop
+/// is used. Stops should be sorted by offset.
+#[derive(Clone)]
+pub enum Paint {
+	/// Colors the whole shape with one color.
+	Solid(Color),
+	/// A gradient along the line from the given start point
+	/// to the given end point.
+	LinearGradient(Point, Point, Vec<GradientStop>),
+	/// A gradient that spreads outwards from the given center point
+	/// to the given radius.
+	RadialGradient(Point, f32, Vec<GradientStop>),
+}
+
+impl Paint {
+	/// Gets the color of the paint at a point.
+	pub fn color_at(&self, point: Point) -> Color {
+		match self {
+			Paint::Solid(color) => *color,
+			Paint::LinearGradient(start, end, stops) => {
+				let dx = end.x - start.x;
+				let dy = end.y - start.y;
+				let length_squared = dx * dx + dy * dy;
+				let offset = if length_squared > 0.0 {
+					((point.x - start.x) * dx + (point.y - start.y) * dy) / length_squared
+				} else {
+					0.0
+				};
+				Self::gradient_color(stops, offset)
+			}
+			Paint::RadialGradient(center, radius, stops) => {
+				let distance = (point.x - center.x).hypot(point.y - center.y);
+				let offset = if *radius > 0.0 {
+					distance / radius
+				} else {
+					0.0
+				};
+				Self::gradient_color(stops, offset)
+			}
+		}
+	}
+
+	fn gradient_color(stops: &[GradientStop], offset: f32) -> Color {
+		let (first, last) = match (stops.first(), stops.last()) {
+			(Some(first), Some(last)) => (first, last),
+			_ => return Color::new(0.0, 0.0, 0.0, 0.0),
+		};
+		if offset <= first.offset {
+			return first.color;
+		}
+		for pair in stops.windows(2) {
+			let (previous, next) = (pair[0], pair[1]);
+			if offset <= next.offset {
+				let span = next.offset - previous.offset;
+				if span <= 0.0 {
+					return next.color;
+				}
+				return previous
+					.color
+					.lerp(next.color, (offset - previous.offset) / span);
+			}
+		}
+		last.color
+	}
+}
+
+impl From<Color> for Paint {
+	fn from(color: Color) -> Self {
+		Paint::Solid(color)
+	}
+}
+
+/// The shape at the ends of an open line.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LineCap {
+	/// The line ends exactly at its end points.
+	Butt,
+	/// The line ends with a half circle around each end point.
+	Round,
+	/// The line ends with a half square around each end point.
+	Square,
+}
+
+/// The shape of the corners where two lines meet.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum LineJoin {
+	/// The outer edges of the lines are extended until they meet.
+	///
+	/// If they would meet too far away from the corner (based on
+	/// the stroke's miter limit), a bevel join is used instead.
+	Miter,
+	/// The corner is rounded off.
+	Round,
+	/// The corner is cut off with a straight line.
+	Bevel,
+}
+
+/// How the outline of a shape is drawn.
+#[derive(Clone, Debug)]
+pub struct Stroke {
+	/// The width of the outline.
+	pub width: f32,
+	/// The shape at the ends of open lines.
+	pub cap: LineCap,
+	/// The shape of the corners where lines meet.
+	pub join: LineJoin,
+	/// The longest a miter join can be (relative to the width
+	/// of the outline) before it's drawn as a bevel join instead.
+	pub miter_limit: f32,
+	/// The lengths of alternating dashes and gaps, or an empty list
+	/// for a solid line.
+	///
+	/// If there are an odd number of lengths, the list is repeated
+	/// to get an even number.
+	pub dashes: Vec<f32>,
+	/// How far into the dash pattern the outline starts.
+	pub dash_offset: f32,
+}
+
+impl Stroke {
+	/// Creates a new solid stroke with butt caps and miter joins.
+	pub fn new(width: f32) -> Self {
+		Self {
+			width,
+			cap: LineCap::Butt,
+			join: LineJoin::Miter,
+			miter_limit: 4.0,
+			dashes: vec![],
+			dash_offset: 0.0,
+		}
+	}
+}
+
+/// How to draw a shape.
+#[derive(Clone)]
 pub enum DrawMode {
-	/// Draws a shape with a solid color.
+	/// Draws a shape filled in.
 	Fill,
-	/// Draws a shape as an outline with the given width.
-	Stroke(f32),
+	/// Draws a shape as an outline.
+	Stroke(Stroke),
 }
 
 /// The visual properties of a shape.
-#[derive(Copy, Clone)]
+#[derive(Clone)]
 pub struct Style {
 	/// How the shape is drawn.
 	pub mode: DrawMode,
-	/// The color of the shape.
-	pub color: Color,
+	/// How the shape is colored.
+	pub paint: Paint,
+}
+
+/// The radius of each corner of a rounded rectangle.
+#[derive(Copy, Clone, Debug)]
+pub struct CornerRadii {
+	/// The radius of the top-left corner.
+	pub top_left: f32,
+	/// The radius of the top-right corner.
+	pub top_right: f32,
+	/// The radius of the bottom-right corner.
+	pub bottom_right: f32,
+	/// The radius of the bottom-left corner.
+	pub bottom_left: f32,
+}
+
+impl CornerRadii {
+	/// Creates a new set of corner radii.
+	pub fn new(top_left: f32, top_right: f32, bottom_right: f32, bottom_left: f32) -> Self {
+		Self {
+			top_left,
+			top_right,
+			bottom_right,
+			bottom_left,
+		}
+	}
+
+	/// Creates a set of corner radii where every corner
+	/// has the same radius.
+	pub fn uniform(radius: f32) -> Self {
+		Self::new(radius, radius, radius, radius)
+	}
 }
 
 /// The kinds of arcs that can be drawn.
 ///
 /// The arc kinds correspond to the [ArcTypes in LÖVE](https://love2d.org/wiki/ArcType).
+#[derive(Copy, Clone)]
 pub enum ArcKind {
 	Pie,
 	Open,
@@ -53,6 +244,7 @@ pub enum ArcKind {
 }
 
 /// How an object is aligned on an axis.
+#[derive(Copy, Clone)]
 pub enum Alignment {
 	/// The start of the object is placed at the given position.
 	Start,
@@ -77,6 +269,7 @@ impl Alignment {
 }
 
 /// The visual properties of a piece of text.
+#[derive(Clone)]
 pub struct TextStyle {
 	/// The index of the font that should be used.
 	pub font_id: usize,
@@ -90,10 +283,35 @@ pub struct TextStyle {
 	pub color: Color,
 }
 
+/// How an image is sampled when it's drawn at a different size
+/// than its original size.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ImageFilter {
+	/// Blends neighboring pixels together, which looks smooth.
+	Linear,
+	/// Uses the closest pixel, which keeps pixel art sharp.
+	Nearest,
+}
+
+/// The visual properties of an image.
+#[derive(Copy, Clone)]
+pub struct ImageStyle {
+	/// The color to multiply each pixel of the image by.
+	///
+	/// Use white to draw the image with its original colors.
+	pub tint: Color,
+	/// How the image is sampled when it's scaled.
+	pub filter: ImageFilter,
+}
+
 /// Represents a drawing task for the backend to complete.
+#[derive(Clone)]
 pub enum DrawOperation {
 	/// A task to draw a rectangle with the given bounds and style.
 	Rectangle(Rectangle, Style),
+	/// A task to draw a rectangle with rounded corners with the given
+	/// bounds, corner radii, and style.
+	RoundedRectangle(Rectangle, CornerRadii, Style),
 	/// A task to draw a circle with the given center, radius, and style.
 	Circle(Point, f32, Style),
 	/// A task to draw an arc with the given `ArcKind`, center, radius,
@@ -105,8 +323,29 @@ pub enum DrawOperation {
 	/// A task to draw a polygon made of the given points
 	/// with the given style.
 	Polygon(Vec<Point>, Style),
+	/// A task to draw a path made of lines and curves with the
+	/// given style.
+	Path(Path, Style),
 	/// A task to draw text with the given string, position, and style.
 	Text(String, Point, TextStyle),
+	/// A task to draw part of an image with the given image ID, source
+	/// rectangle (in pixels, or `None` for the whole image), destination
+	/// rectangle, and style.
+	Image(ImageId, Option<Rectangle>, Rectangle, ImageStyle),
+	/// A task to apply the given transform (on top of any transforms
+	/// that are already applied) to everything drawn until the matching
+	/// `PopTransform`.
+	PushTransform(Transform),
+	/// A task to stop applying the most recently pushed transform.
+	PopTransform,
+	/// A task to only draw inside the given rectangle (and inside any
+	/// clip rectangles that are already pushed) until the matching
+	/// `PopClip`.
+	///
+	/// The rectangle isn't affected by transforms.
+	PushClip(Rectangle),
+	/// A task to stop clipping to the most recently pushed rectangle.
+	PopClip,
 }
 
 /// A surface that a `ControlBehavior` can draw to.
@@ -118,12 +357,74 @@ pub enum DrawOperation {
 pub struct Canvas {
 	/// The list of drawing tasks that the backend should complete.
 	pub operations: Vec<DrawOperation>,
+	transform_depth: usize,
+	clip_depth: usize,
 }
 
 impl Canvas {
 	/// Creates a new `Canvas`.
 	pub fn new() -> Self {
-		Self { operations: vec![] }
+		Self {
+			operations: vec![],
+			transform_depth: 0,
+			clip_depth: 0,
+		}
+	}
+
+	/// Applies a transform to everything drawn until `pop_transform`
+	/// is called.
+	///
+	/// Transforms can be nested, in which case the new transform
+	/// is applied first, followed by the transforms that were
+	/// already pushed.
+	pub fn push_transform(&mut self, transform: Transform) {
+		self.transform_depth += 1;
+		self.operations
+			.push(DrawOperation::PushTransform(transform));
+	}
+
+	/// Stops applying the most recently pushed transform.
+	///
+	/// Does nothing if there are no transforms to pop.
+	pub fn pop_transform(&mut self) {
+		if self.transform_depth == 0 {
+			return;
+		}
+		self.transform_depth -= 1;
+		self.operations.push(DrawOperation::PopTransform);
+	}
+
+	/// Only draws inside the given rectangle until `pop_clip`
+	/// is called.
+	///
+	/// Clip rectangles can be nested, in which case only the area
+	/// inside all of them is drawn to. Clip rectangles are in the
+	/// GUI's coordinates, so they aren't affected by transforms.
+	pub fn push_clip(&mut self, rectangle: Rectangle) {
+		self.clip_depth += 1;
+		self.operations.push(DrawOperation::PushClip(rectangle));
+	}
+
+	/// Stops clipping to the most recently pushed rectangle.
+	///
+	/// Does nothing if there are no clip rectangles to pop.
+	pub fn pop_clip(&mut self) {
+		if self.clip_depth == 0 {
+			return;
+		}
+		self.clip_depth -= 1;
+		self.operations.push(DrawOperation::PopClip);
+	}
+
+	/// Pops any transforms and clip rectangles that were pushed
+	/// but never popped.
+	pub(crate) fn restore(&mut self) {
+		while self.transform_depth > 0 {
+			self.pop_transform();
+		}
+		while self.clip_depth > 0 {
+			self.pop_clip();
+		}
 	}
 
 	/// Draws a rectangle.
@@ -132,6 +433,21 @@ impl Canvas {
 			.push(DrawOperation::Rectangle(rectangle, style));
 	}
 
+	/// Draws a rectangle with rounded corners.
+	///
+	/// If the radii of two adjacent corners add up to more than the
+	/// length of the side between them, the radii are scaled down
+	/// to fit.
+	pub fn draw_rounded_rectangle(
+		&mut self,
+		rectangle: Rectangle,
+		radii: CornerRadii,
+		style: Style,
+	) {
+		self.operations
+			.push(DrawOperation::RoundedRectangle(rectangle, radii, style));
+	}
+
 	/// Draws a circle.
 	pub fn draw_circle(&mut self, position: Point, radius: f32, style: Style) {
 		self.operations
@@ -163,9 +479,88 @@ impl Canvas {
 		self.operations.push(DrawOperation::Polygon(points, style));
 	}
 
+	/// Draws a path made of lines and curves.
+	///
+	/// When filled, each subpath is filled separately.
+	pub fn draw_path(&mut self, path: Path, style: Style) {
+		self.operations.push(DrawOperation::Path(path, style));
+	}
+
 	/// Draws text.
 	pub fn draw_text(&mut self, text: String, position: Point, style: TextStyle) {
 		self.operations
 			.push(DrawOperation::Text(text, position, style));
 	}
+
+	/// Draws an image, stretching it to fill the destination rectangle.
+	///
+	/// `source` is the part of the image to draw (in pixels), or `None`
+	/// to draw the whole image.
+	pub fn draw_image(
+		&mut self,
+		image_id: ImageId,
+		source: Option<Rectangle>,
+		destination: Rectangle,
+		style: ImageStyle,
+	) {
+		self.operations
+			.push(DrawOperation::Image(image_id, source, destination, style));
+	}
+
+	/// Draws the frame of a filmstrip that represents a value from 0-1,
+	/// stretching it to fill the destination rectangle.
+	pub fn draw_filmstrip(
+		&mut self,
+		filmstrip: &Filmstrip,
+		value: f32,
+		destination: Rectangle,
+		style: ImageStyle,
+	) {
+		let (image_id, source) = filmstrip.frame_source(filmstrip.frame_for_value(value));
+		self.draw_image(image_id, Some(source), destination, style);
+	}
+
+	/// Draws a vector image, scaling it to fit inside the destination
+	/// rectangle and centering it.
+	///
+	/// Shapes in the image that are colored with `currentColor` use
+	/// the given color, which is useful for icons that change color.
+	pub fn draw_svg(&mut self, svg: &Svg, destination: Rectangle, current_color: Color) {
+		let view_box = svg.view_box;
+		let scale = (destination.width / view_box.width).min(destination.height / view_box.height);
+		let x = destination.x + (destination.width - view_box.width * scale) / 2.0;
+		let y = destination.y + (destination.height - view_box.height * scale) / 2.0;
+		self.push_transform(
+			Transform::translation(-view_box.x, -view_box.y)
+				.scaled(scale, scale)
+				.translated(x, y),
+		);
+		for shape in &svg.shapes {
+			if let Some(transform) = shape.transform {
+				self.push_transform(transform);
+			}
+			if let Some(paint) = &shape.fill {
+				self.draw_path(
+					shape.path.clone(),
+					Style {
+						mode: DrawMode::Fill,
+						paint: Paint::Solid(paint.resolve(current_color)),
+					},
+				);
+			}
+			if let Some((paint, stroke)) = &shape.stroke {
+				self.draw_path(
+					shape.path.clone(),
+					Style {
+						mode: DrawMode::Stroke(stroke.clone()),
+						paint: Paint::Solid(paint.resolve(current_color)),
+					},
+				);
+			}
+			if shape.transform.is_some() {
+				self.pop_transform();
+			}
+		}
+		self.pop_transform();
+	}
 }
@@ -1,12 +1,31 @@
-use super::{geometry::Rectangle, input::MouseButton};
+use super::{
+	animation::{Animatable, AnyValueAnimation},
+	geometry::Rectangle,
+	gui::ControlId,
+	input::MouseButton,
+};
 use enum_map::{enum_map, EnumMap};
+use std::collections::HashMap;
 
 /// The configuration for a new control.
 pub struct ControlSettings {
-	/// The rectangular bounds of the control.
+	/// The rectangular bounds of the control, relative to
+	/// the top-left corner of its parent.
 	pub rectangle: Rectangle,
 	/// The height of the control.
+	///
+	/// Controls with a greater height are drawn on top of
+	/// (and receive mouse input before) controls with a lower height.
 	pub height: i32,
+	/// The control this control should be nested in, if any.
+	///
+	/// Child controls are positioned relative to their parent,
+	/// and they're hidden when their parent is hidden.
+	pub parent: Option<ControlId>,
+	/// Whether the control (and its children) should be visible.
+	pub visible: bool,
+	/// Whether the control can receive keyboard focus.
+	pub focusable: bool,
 }
 
 /// A rectangular space in the GUI that can be interacted with.
@@ -17,15 +36,34 @@ pub struct ControlSettings {
 /// which define how the control behaves and looks. A control
 /// doesn't do anything on its own.
 pub struct Control {
-	/// The rectangular bounds of the control.
+	/// The rectangular bounds of the control, relative to
+	/// the top-left corner of its parent.
 	pub rectangle: Rectangle,
+	/// The rectangular bounds of the control, relative to
+	/// the top-left corner of the GUI.
+	///
+	/// This is updated by the GUI, so changing it has no effect.
+	pub absolute_rectangle: Rectangle,
 	/// The height of the control.
+	///
+	/// Controls with a greater height are drawn on top of
+	/// (and receive mouse input before) controls with a lower height.
 	pub height: i32,
 	/// Whether the mouse is currently hovering over the control.
 	pub is_hovered: bool,
 	/// Whether the user is "holding down" the control with the given
 	/// `MouseButton`.
 	pub is_held: EnumMap<MouseButton, bool>,
+	/// Whether the control (and its children) should be visible.
+	///
+	/// Hidden controls can't be interacted with.
+	pub visible: bool,
+	/// Whether the control can receive keyboard focus.
+	pub focusable: bool,
+	/// Whether the control currently has keyboard focus.
+	pub is_focused: bool,
+	parent: Option<ControlId>,
+	animations: HashMap<&'static str, Box<dyn AnyValueAnimation>>,
 }
 
 impl Control {
@@ -33,6 +71,7 @@ impl Control {
 	pub fn new(settings: &ControlSettings) -> Self {
 		Self {
 			rectangle: settings.rectangle,
+			absolute_rectangle: settings.rectangle,
 			height: settings.height,
 			is_hovered: false,
 			is_held: enum_map! {
@@ -40,6 +79,54 @@ impl Control {
 				MouseButton::Middle => false,
 				MouseButton::Right => false,
 			},
+			visible: settings.visible,
+			focusable: settings.focusable,
+			is_focused: false,
+			parent: settings.parent,
+			animations: HashMap::new(),
 		}
 	}
+
+	/// Returns the ID of the control's parent, if it has one.
+	pub fn parent(&self) -> Option<ControlId> {
+		self.parent
+	}
+
+	/// Gets the current value of an animation that was started
+	/// with `Controls::start_animation`.
+	///
+	/// Finished animations keep their final value until they're replaced.
+	/// Returns `None` if there's no animation with the given key, or if
+	/// the animation doesn't animate values of type `T`.
+	pub fn animation_value<T: Animatable + 'static>(&self, key: &str) -> Option<T> {
+		self.animations.get(key)?.value()
+	}
+
+	/// Returns `true` if any of the control's animations are still running.
+	pub fn is_animating(&self) -> bool {
+		self.animations
+			.values()
+			.any(|animation| !animation.is_finished())
+	}
+
+	pub(crate) fn start_animation(
+		&mut self,
+		key: &'static str,
+		animation: Box<dyn AnyValueAnimation>,
+	) {
+		self.animations.insert(key, animation);
+	}
+
+	/// Advances the control's running animations by `dt` seconds.
+	/// Returns `true` if any of them were running.
+	pub(crate) fn update_animations(&mut self, dt: f32) -> bool {
+		let mut animating = false;
+		for animation in self.animations.values_mut() {
+			if !animation.is_finished() {
+				animation.update(dt);
+				animating = true;
+			}
+		}
+		animating
+	}
 }
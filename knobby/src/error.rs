@@ -11,3 +11,27 @@ impl Display for InvalidFontError {
 		Ok(())
 	}
 }
+
+#[derive(Debug)]
+pub struct InvalidImageError;
+
+impl Error for InvalidImageError {}
+
+impl Display for InvalidImageError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str("Invalid image data")?;
+		Ok(())
+	}
+}
+
+#[derive(Debug)]
+pub struct InvalidSvgError;
+
+impl Error for InvalidSvgError {}
+
+impl Display for InvalidSvgError {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str("Invalid SVG data")?;
+		Ok(())
+	}
+}
@@ -1,16 +1,25 @@
-use crate::{gui::ControlId, input::MouseButton};
+use crate::{
+	gui::ControlId,
+	input::{Key, Modifiers, MouseButton, ScrollUnit},
+};
 
 /// Represents something that happened in the GUI or the
 /// audio thread.
 #[derive(Copy, Clone)]
 pub enum Event<CustomEvent> {
+	/// Emitted when a control with the given ID is added to the GUI.
+	Added(ControlId),
+	/// Emitted when a control with the given ID is about to be
+	/// removed from the GUI.
+	Removed(ControlId),
 	/// Emitted when a control is hovered.
 	///
 	/// Contains:
 	/// - the ID of the control
 	/// - the x position of the mouse relative to the left side of the control
 	/// - the y position of the mouse relative to the top of the control
-	Hover(ControlId, f32, f32),
+	/// - the modifier keys that were held down
+	Hover(ControlId, f32, f32, Modifiers),
 	/// Emitted when a control with the given ID is no longer hovered.
 	Unhover(ControlId),
 	/// Emitted when a control starts being "held down".
@@ -20,7 +29,8 @@ pub enum Event<CustomEvent> {
 	/// - the button used to hold the control
 	/// - the x position of the mouse relative to the left side of the control
 	/// - the y position of the mouse relative to the top of the control
-	Press(ControlId, MouseButton, f32, f32),
+	/// - the modifier keys that were held down
+	Press(ControlId, MouseButton, f32, f32, Modifiers),
 	/// Emitted when a control stops being "held down".
 	///
 	/// Contains:
@@ -28,7 +38,8 @@ pub enum Event<CustomEvent> {
 	/// - the button previously used to hold the control
 	/// - the x position of the mouse relative to the left side of the control
 	/// - the y position of the mouse relative to the top of the control
-	Release(ControlId, MouseButton, f32, f32),
+	/// - the modifier keys that were held down
+	Release(ControlId, MouseButton, f32, f32, Modifiers),
 	/// Emitted when a control is clicked.
 	///
 	/// More specifically, this is emitted when a control
@@ -44,7 +55,23 @@ pub enum Event<CustomEvent> {
 	/// - the button used to hold the control
 	/// - the x position of the mouse relative to the left side of the control
 	/// - the y position of the mouse relative to the top of the control
-	Click(ControlId, MouseButton, f32, f32),
+	/// - the modifier keys that were held down
+	/// - the number of times the control was clicked in quick succession
+	///   (1 for a single click, 2 for a double click, etc.)
+	Click(ControlId, MouseButton, f32, f32, Modifiers, u32),
+	/// Emitted when a control is clicked twice in quick succession.
+	///
+	/// This is emitted after the second `Click` event. How quickly
+	/// the clicks have to happen is determined by the GUI's
+	/// `double_click_interval` and `double_click_distance`.
+	///
+	/// Contains:
+	/// - the ID of the control
+	/// - the button used to click the control
+	/// - the x position of the mouse relative to the left side of the control
+	/// - the y position of the mouse relative to the top of the control
+	/// - the modifier keys that were held down
+	DoubleClick(ControlId, MouseButton, f32, f32, Modifiers),
 	/// Emitted when a control is dragged.
 	///
 	/// Contains:
@@ -54,7 +81,46 @@ pub enum Event<CustomEvent> {
 	/// - the y position of the mouse relative to the top of the control
 	/// - the amount the mouse moved on the x-axis
 	/// - the amount the mouse moved on the y-axis
-	Drag(ControlId, MouseButton, f32, f32, f32, f32),
+	/// - the modifier keys that were held down
+	Drag(ControlId, MouseButton, f32, f32, f32, f32, Modifiers),
+	/// Emitted when the mouse wheel or trackpad is scrolled
+	/// while a control is hovered.
+	///
+	/// Contains:
+	/// - the ID of the control
+	/// - the amount scrolled on the x-axis (positive values mean right)
+	/// - the amount scrolled on the y-axis (positive values mean up)
+	/// - the unit the scroll amounts are measured in
+	/// - the modifier keys that were held down
+	Scroll(ControlId, f32, f32, ScrollUnit, Modifiers),
+	/// Emitted when a control receives keyboard focus.
+	Focus(ControlId),
+	/// Emitted when a control with the given ID loses keyboard focus.
+	Blur(ControlId),
+	/// Emitted when a key is pressed while a control is focused.
+	///
+	/// Contains:
+	/// - the ID of the focused control
+	/// - the key that was pressed
+	/// - the modifier keys that were held down
+	KeyDown(ControlId, Key, Modifiers),
+	/// Emitted when a key is released while a control is focused.
+	///
+	/// Contains:
+	/// - the ID of the focused control
+	/// - the key that was released
+	/// - the modifier keys that were held down
+	KeyUp(ControlId, Key, Modifiers),
+	/// Emitted when text is typed while a control is focused.
+	///
+	/// Contains:
+	/// - the ID of the focused control
+	/// - the character that was typed
+	TextInput(ControlId, char),
+	/// Emitted every frame to behaviors that want updates.
+	///
+	/// Contains the number of seconds since the last frame.
+	Update(f32),
 	/// Emitted when a parameter is changed or should be changed.
 	///
 	/// When emitted to the GUI, this represents a parameter that
@@ -69,6 +135,21 @@ pub enum Event<CustomEvent> {
 	/// Emitted to tell the audio thread that the parameter with the given
 	/// ID should be reset to its default value.
 	ResetParameter(i32),
+	/// Emitted to tell the audio thread that the user started changing
+	/// the parameter with the given index (for example, by grabbing
+	/// a knob).
+	///
+	/// Hosts use this to know when to start recording automation.
+	/// The GUI makes sure that every `BeginParameterGesture` event
+	/// is followed by an `EndParameterGesture` event for the same
+	/// parameter, even if the control that started the gesture is
+	/// removed or released unexpectedly. Gestures started in response
+	/// to a scroll or key event end as soon as the event has been
+	/// handled, unless the control is also being held down.
+	BeginParameterGesture(i32),
+	/// Emitted to tell the audio thread that the user stopped changing
+	/// the parameter with the given index.
+	EndParameterGesture(i32),
 	/// A user-defined event.
 	Custom(CustomEvent),
 }
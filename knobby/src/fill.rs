@@ -0,0 +1,281 @@
+use crate::{
+	geometry::Point,
+	path::{FillRule, Subpath},
+};
+
+/// Edges (and bands between edges) shorter than this are skipped.
+const EPSILON: f32 = 0.0001;
+
+/// Converts the outline of a shape to triangles that cover
+/// the inside of the shape.
+///
+/// Every subpath is treated as closed, and the fill rule decides
+/// whether overlapping parts of the subpaths (including holes and
+/// the loops of self-intersecting outlines) are inside the shape.
+pub fn fill_triangles(subpaths: &[Subpath], fill_rule: FillRule) -> Vec<[Point; 3]> {
+	let edges = edges(subpaths);
+	// split the shape into horizontal bands where no edges start, end,
+	// or cross each other, so the edges crossing each band can be
+	// sorted from left to right
+	let mut band_edges: Vec<f32> = vec![];
+	for (i, edge) in edges.iter().enumerate() {
+		band_edges.push(edge.top.y);
+		band_edges.push(edge.bottom.y);
+		for other in &edges[i + 1..] {
+			if let Some(y) = edge.intersection(other) {
+				band_edges.push(y);
+			}
+		}
+	}
+	band_edges.sort_by(|a, b| a.total_cmp(b));
+	band_edges.dedup_by(|a, b| (*a - *b).abs() < EPSILON);
+	let mut triangles = vec![];
+	let mut crossings: Vec<(f32, f32, i32)> = vec![];
+	for band in band_edges.windows(2) {
+		let (top, bottom) = (band[0], band[1]);
+		let middle = (top + bottom) / 2.0;
+		crossings.clear();
+		crossings.extend(
+			edges
+				.iter()
+				.filter(|edge| edge.top.y < middle && edge.bottom.y > middle)
+				.map(|edge| (edge.x_at(top), edge.x_at(bottom), edge.winding)),
+		);
+		crossings.sort_by(|a, b| (a.0 + a.1).total_cmp(&(b.0 + b.1)));
+		// fill the spans between edges where the winding number
+		// says we're inside the shape
+		let mut winding = 0;
+		let mut span_start = None;
+		for (top_x, bottom_x, edge_winding) in crossings.iter().copied() {
+			let was_inside = fill_rule.is_inside(winding);
+			winding += edge_winding;
+			let is_inside = fill_rule.is_inside(winding);
+			if !was_inside && is_inside {
+				span_start = Some((top_x, bottom_x));
+			} else if was_inside && !is_inside {
+				if let Some((start_top_x, start_bottom_x)) = span_start.take() {
+					let a = Point::new(start_top_x, top);
+					let b = Point::new(top_x, top);
+					let c = Point::new(bottom_x, bottom);
+					let d = Point::new(start_bottom_x, bottom);
+					if top_x - start_top_x > EPSILON {
+						triangles.push([a, b, c]);
+					}
+					if bottom_x - start_bottom_x > EPSILON {
+						triangles.push([a, c, d]);
+					}
+				}
+			}
+		}
+	}
+	triangles
+}
+
+impl FillRule {
+	fn is_inside(self, winding: i32) -> bool {
+		match self {
+			FillRule::NonZero => winding != 0,
+			FillRule::EvenOdd => winding % 2 != 0,
+		}
+	}
+}
+
+/// A line in the outline of a shape.
+struct Edge {
+	top: Point,
+	bottom: Point,
+	/// 1 if the line goes down, -1 if it goes up.
+	winding: i32,
+}
+
+impl Edge {
+	fn x_at(&self, y: f32) -> f32 {
+		let amount = (y - self.top.y) / (self.bottom.y - self.top.y);
+		self.top.x + (self.bottom.x - self.top.x) * amount.clamp(0.0, 1.0)
+	}
+
+	/// Returns the y position where this edge crosses another edge,
+	/// if they cross somewhere other than their ends.
+	fn intersection(&self, other: &Edge) -> Option<f32> {
+		let top = self.top.y.max(other.top.y);
+		let bottom = self.bottom.y.min(other.bottom.y);
+		if bottom - top < EPSILON {
+			return None;
+		}
+		// the horizontal distance between the edges changes linearly,
+		// so they cross where it changes sign
+		let top_distance = self.x_at(top) - other.x_at(top);
+		let bottom_distance = self.x_at(bottom) - other.x_at(bottom);
+		if top_distance * bottom_distance >= 0.0 {
+			return None;
+		}
+		let amount = top_distance / (top_distance - bottom_distance);
+		Some(top + (bottom - top) * amount)
+	}
+}
+
+/// Returns the non-horizontal edges of the subpaths, closing
+/// each subpath with an edge from its last point to its first point.
+///
+/// Points with NaN or infinite coordinates (which can come from dividing
+/// by zero when building a path) are left out.
+fn edges(subpaths: &[Subpath]) -> Vec<Edge> {
+	let mut edges = vec![];
+	for subpath in subpaths {
+		let points: Vec<Point> = subpath
+			.points
+			.iter()
+			.copied()
+			.filter(|point| point.x.is_finite() && point.y.is_finite())
+			.collect();
+		for i in 0..points.len() {
+			let start = points[i];
+			let end = points[(i + 1) % points.len()];
+			if (end.y - start.y).abs() < EPSILON {
+				continue;
+			}
+			edges.push(if start.y < end.y {
+				Edge {
+					top: start,
+					bottom: end,
+					winding: 1,
+				}
+			} else {
+				Edge {
+					top: end,
+					bottom: start,
+					winding: -1,
+				}
+			});
+		}
+	}
+	edges
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::path::Path;
+
+	fn subpath(points: &[(f32, f32)]) -> Subpath {
+		Subpath {
+			points: points.iter().map(|(x, y)| Point::new(*x, *y)).collect(),
+			closed: true,
+		}
+	}
+
+	fn area(triangles: &[[Point; 3]]) -> f32 {
+		triangles
+			.iter()
+			.map(|[a, b, c]| ((b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)).abs() / 2.0)
+			.sum()
+	}
+
+	fn square(x: f32, y: f32, size: f32, clockwise: bool) -> Subpath {
+		let mut points = vec![(x, y), (x + size, y), (x + size, y + size), (x, y + size)];
+		if !clockwise {
+			points.reverse();
+		}
+		subpath(&points)
+	}
+
+	fn assert_close(actual: f32, expected: f32) {
+		assert!(
+			(actual - expected).abs() < 0.01,
+			"expected {}, got {}",
+			expected,
+			actual
+		);
+	}
+
+	#[test]
+	fn fills_simple_shapes() {
+		let triangles = fill_triangles(&[square(0.0, 0.0, 10.0, true)], FillRule::NonZero);
+		assert_close(area(&triangles), 100.0);
+		let triangle = subpath(&[(0.0, 0.0), (10.0, 10.0), (-10.0, 10.0)]);
+		assert_close(area(&fill_triangles(&[triangle], FillRule::EvenOdd)), 100.0);
+	}
+
+	#[test]
+	fn skips_degenerate_shapes() {
+		assert!(fill_triangles(&[], FillRule::NonZero).is_empty());
+		let line = subpath(&[(0.0, 0.0), (10.0, 10.0)]);
+		assert!(fill_triangles(&[line], FillRule::NonZero).is_empty());
+		let flat = subpath(&[(0.0, 0.0), (10.0, 0.0), (20.0, 0.0)]);
+		assert!(fill_triangles(&[flat], FillRule::NonZero).is_empty());
+	}
+
+	#[test]
+	fn skips_points_that_are_not_finite() {
+		let square_with_bad_points = subpath(&[
+			(0.0, 0.0),
+			(f32::NAN, 5.0),
+			(10.0, 0.0),
+			(10.0, 10.0),
+			(f32::INFINITY, f32::NEG_INFINITY),
+			(0.0, 10.0),
+			(0.0, f32::NAN),
+		]);
+		let triangles = fill_triangles(&[square_with_bad_points], FillRule::NonZero);
+		assert_close(area(&triangles), 100.0);
+		let only_bad_points = subpath(&[(f32::NAN, f32::NAN), (f32::INFINITY, 0.0)]);
+		assert!(fill_triangles(&[only_bad_points], FillRule::EvenOdd).is_empty());
+		// like a path with a control point calculated by dividing 0 by 0,
+		// which makes every point on the curve NaN, so only the triangle
+		// made by the rest of the points is filled
+		let mut path = Path::new();
+		path.move_to(Point::new(0.0, 0.0))
+			.line_to(Point::new(10.0, 0.0))
+			.quad_to(Point::new(f32::NAN, f32::NAN), Point::new(10.0, 10.0))
+			.line_to(Point::new(f32::INFINITY, 5.0))
+			.line_to(Point::new(0.0, 10.0))
+			.close();
+		let triangles = fill_triangles(&path.flatten(0.1), path.fill_rule());
+		assert_close(area(&triangles), 50.0);
+	}
+
+	#[test]
+	fn holes_depend_on_the_fill_rule() {
+		// a hole that winds the opposite way is a hole with either rule
+		let opposite = [square(0.0, 0.0, 10.0, true), square(2.0, 2.0, 6.0, false)];
+		assert_close(area(&fill_triangles(&opposite, FillRule::NonZero)), 64.0);
+		assert_close(area(&fill_triangles(&opposite, FillRule::EvenOdd)), 64.0);
+		// a hole that winds the same way is only a hole with even-odd
+		let same = [square(0.0, 0.0, 10.0, true), square(2.0, 2.0, 6.0, true)];
+		assert_close(area(&fill_triangles(&same, FillRule::NonZero)), 100.0);
+		assert_close(area(&fill_triangles(&same, FillRule::EvenOdd)), 64.0);
+	}
+
+	#[test]
+	fn fills_self_intersecting_outlines() {
+		// a bowtie made of two triangles that meet in the middle
+		let bowtie = subpath(&[(0.0, 0.0), (10.0, 10.0), (10.0, 0.0), (0.0, 10.0)]);
+		assert_close(
+			area(&fill_triangles(
+				std::slice::from_ref(&bowtie),
+				FillRule::NonZero,
+			)),
+			50.0,
+		);
+		assert_close(area(&fill_triangles(&[bowtie], FillRule::EvenOdd)), 50.0);
+		// a five-pointed star, whose center has a winding number of 2
+		let star: Vec<(f32, f32)> = (0..5)
+			.map(|i| {
+				let angle = (i * 2) as f32 * std::f32::consts::PI * 2.0 / 5.0;
+				(angle.cos() * 10.0, angle.sin() * 10.0)
+			})
+			.collect();
+		let star = subpath(&star);
+		let non_zero = area(&fill_triangles(
+			std::slice::from_ref(&star),
+			FillRule::NonZero,
+		));
+		let even_odd = area(&fill_triangles(&[star], FillRule::EvenOdd));
+		// the pentagon in the middle has a circumradius of
+		// 10 * cos(72°) / cos(36°)
+		let inner_radius =
+			10.0 * (0.4 * std::f32::consts::PI).cos() / (0.2 * std::f32::consts::PI).cos();
+		let pentagon = 2.5 * inner_radius * inner_radius * (0.4 * std::f32::consts::PI).sin();
+		assert_close(non_zero - even_odd, pentagon);
+	}
+}
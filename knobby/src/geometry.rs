@@ -85,6 +85,23 @@ impl Rectangle {
 			&& point.y <= self.y + self.height
 	}
 
+	/// Returns the area where this rectangle overlaps another one.
+	///
+	/// If the rectangles don't overlap, the returned rectangle
+	/// has a width and height of 0.
+	pub fn intersection(&self, other: &Rectangle) -> Self {
+		let x = self.x.max(other.x);
+		let y = self.y.max(other.y);
+		let right = (self.x + self.width).min(other.x + other.width);
+		let bottom = (self.y + self.height).min(other.y + other.height);
+		Self {
+			x,
+			y,
+			width: (right - x).max(0.0),
+			height: (bottom - y).max(0.0),
+		}
+	}
+
 	/// Gets the center of the rectangle.
 	pub fn get_center(&self) -> Point {
 		Point::new(self.x + self.width / 2.0, self.y + self.height / 2.0)
@@ -164,3 +181,111 @@ impl Rectangle {
 		}
 	}
 }
+
+/// A 2D affine transformation, like a translation, rotation,
+/// scale, or any combination of them.
+///
+/// A point `(x, y)` is transformed to
+/// `(a * x + c * y + e, b * x + d * y + f)`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transform {
+	pub a: f32,
+	pub b: f32,
+	pub c: f32,
+	pub d: f32,
+	pub e: f32,
+	pub f: f32,
+}
+
+impl Transform {
+	/// Creates a transform that leaves points where they are.
+	pub fn identity() -> Self {
+		Self {
+			a: 1.0,
+			b: 0.0,
+			c: 0.0,
+			d: 1.0,
+			e: 0.0,
+			f: 0.0,
+		}
+	}
+
+	/// Creates a transform that moves points by the given amount.
+	pub fn translation(dx: f32, dy: f32) -> Self {
+		Self {
+			e: dx,
+			f: dy,
+			..Self::identity()
+		}
+	}
+
+	/// Creates a transform that rotates points clockwise around
+	/// the origin by the given angle (in radians).
+	pub fn rotation(angle: f32) -> Self {
+		let (sin, cos) = angle.sin_cos();
+		Self {
+			a: cos,
+			b: sin,
+			c: -sin,
+			d: cos,
+			e: 0.0,
+			f: 0.0,
+		}
+	}
+
+	/// Creates a transform that rotates points clockwise around
+	/// the given center by the given angle (in radians).
+	pub fn rotation_around(angle: f32, center: Point) -> Self {
+		Self::translation(-center.x, -center.y)
+			.rotated(angle)
+			.translated(center.x, center.y)
+	}
+
+	/// Creates a transform that scales points away from the origin.
+	pub fn scaling(x_amount: f32, y_amount: f32) -> Self {
+		Self {
+			a: x_amount,
+			d: y_amount,
+			..Self::identity()
+		}
+	}
+
+	/// Returns a transform that applies this transform followed
+	/// by another one.
+	pub fn then(&self, other: Transform) -> Self {
+		Self {
+			a: other.a * self.a + other.c * self.b,
+			b: other.b * self.a + other.d * self.b,
+			c: other.a * self.c + other.c * self.d,
+			d: other.b * self.c + other.d * self.d,
+			e: other.a * self.e + other.c * self.f + other.e,
+			f: other.b * self.e + other.d * self.f + other.f,
+		}
+	}
+
+	/// Returns a transform that applies this transform and then
+	/// moves points by the given amount.
+	pub fn translated(&self, dx: f32, dy: f32) -> Self {
+		self.then(Self::translation(dx, dy))
+	}
+
+	/// Returns a transform that applies this transform and then
+	/// rotates points around the origin.
+	pub fn rotated(&self, angle: f32) -> Self {
+		self.then(Self::rotation(angle))
+	}
+
+	/// Returns a transform that applies this transform and then
+	/// scales points away from the origin.
+	pub fn scaled(&self, x_amount: f32, y_amount: f32) -> Self {
+		self.then(Self::scaling(x_amount, y_amount))
+	}
+
+	/// Applies the transform to a point.
+	pub fn transform_point(&self, point: Point) -> Point {
+		Point::new(
+			self.a * point.x + self.c * point.y + self.e,
+			self.b * point.x + self.d * point.y + self.f,
+		)
+	}
+}
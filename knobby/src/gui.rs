@@ -1,15 +1,21 @@
 use crate::{
+	animation::{Animatable, BoxedValueAnimation, ValueAnimation},
+	audio::{AudioBridgeSettings, AudioHandle, GuiConnection},
 	behavior::Behavior,
-	canvas::{Canvas, Color, DrawMode, Style},
+	canvas::{Canvas, DrawOperation},
 	control::{Control, ControlSettings},
-	error::InvalidFontError,
+	error::{InvalidFontError, InvalidImageError, InvalidSvgError},
 	event::Event,
-	geometry::Point,
-	input::MouseButton,
+	geometry::{Point, Rectangle},
+	image::{Filmstrip, FilmstripOrientation, Image},
+	input::{Key, Modifier, Modifiers, MouseButton, ScrollUnit},
+	meter::{MeterLayout, MeterPublisher, Meters},
+	parameter::Parameters,
+	svg::Svg,
 };
 use enum_map::{enum_map, EnumMap};
 use rusttype::Font;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 /// A unqiue identifier for a control.
 pub type ControlId = usize;
@@ -18,6 +24,15 @@ pub type ControlId = usize;
 pub struct Controls {
 	controls: HashMap<ControlId, Control>,
 	next_control_id: ControlId,
+	dirty: HashSet<ControlId>,
+	invalidated_regions: Vec<Rectangle>,
+	/// The controls whose previous area has already been added
+	/// to `invalidated_regions` since the last draw.
+	invalidated_controls: HashSet<ControlId>,
+	/// Changes whenever a control is added, removed, or borrowed
+	/// mutably (and possibly moved or hidden), so the GUI knows when
+	/// the draw order and absolute rectangles have to be worked out again.
+	generation: usize,
 }
 
 impl Controls {
@@ -25,14 +40,163 @@ impl Controls {
 		Self {
 			controls: HashMap::new(),
 			next_control_id: 0,
+			dirty: HashSet::new(),
+			invalidated_regions: vec![],
+			invalidated_controls: HashSet::new(),
+			generation: 0,
 		}
 	}
 
-	fn add(&mut self, settings: &ControlSettings) -> ControlId {
+	/// Adds a control, or returns `None` if its parent
+	/// hasn't been added.
+	fn add(&mut self, settings: &ControlSettings) -> Option<ControlId> {
+		if let Some(parent) = settings.parent {
+			if !self.controls.contains_key(&parent) {
+				return None;
+			}
+		}
 		let id = self.next_control_id;
 		self.next_control_id += 1;
 		self.controls.insert(id, Control::new(settings));
-		id
+		self.dirty.insert(id);
+		self.generation = self.generation.wrapping_add(1);
+		Some(id)
+	}
+
+	fn remove(&mut self, id: &ControlId) -> Option<Control> {
+		self.dirty.remove(id);
+		self.generation = self.generation.wrapping_add(1);
+		self.controls.remove(id)
+	}
+
+	/// Marks a control as dirty, remembering the area it covered
+	/// so that area gets redrawn even if the control moves or
+	/// is removed.
+	///
+	/// The area is remembered the first time the control is invalidated
+	/// each frame, even if the control was already dirty for some
+	/// other reason (like a parameter or meter change).
+	fn invalidate(&mut self, id: ControlId) {
+		if !self.controls.contains_key(&id) {
+			return;
+		}
+		self.dirty.insert(id);
+		if self.invalidated_controls.insert(id) && self.is_visible(&id) {
+			let rectangle = self.get_absolute_rectangle(&id).unwrap();
+			self.invalidated_regions.push(rectangle);
+		}
+	}
+
+	/// Marks a control and all of its descendants as dirty.
+	fn invalidate_subtree(&mut self, id: ControlId) {
+		if !self.controls.contains_key(&id) {
+			return;
+		}
+		self.invalidate(id);
+		for descendant in self.descendants(id) {
+			self.invalidate(descendant);
+		}
+	}
+
+	/// Marks every control as dirty.
+	fn invalidate_all(&mut self) {
+		self.dirty.extend(self.controls.keys());
+	}
+
+	/// Forgets about all of the dirty controls.
+	fn clear_dirty(&mut self) {
+		self.dirty.clear();
+		self.invalidated_regions.clear();
+		self.invalidated_controls.clear();
+	}
+
+	/// Returns the IDs of the direct children of the given control
+	/// (or the top-level controls if `parent` is `None`), sorted
+	/// from bottom to top.
+	///
+	/// Controls with a greater height are placed above controls with
+	/// a lower height. Controls with the same height are ordered by
+	/// when they were added, with newer controls on top.
+	fn sorted_children(&self, parent: Option<ControlId>) -> Vec<ControlId> {
+		let mut ids: Vec<ControlId> = self
+			.controls
+			.iter()
+			.filter(|(_, control)| control.parent() == parent)
+			.map(|(id, _)| *id)
+			.collect();
+		ids.sort_by_key(|id| (self.controls[id].height, *id));
+		ids
+	}
+
+	/// Returns the IDs of all of the visible controls in the order
+	/// they should be drawn.
+	///
+	/// Each control is drawn before its children, and siblings
+	/// are drawn from bottom to top.
+	fn draw_order(&self) -> Vec<ControlId> {
+		// group the controls by parent up front so we don't have to
+		// search the whole list for each control's children
+		let mut children: HashMap<Option<ControlId>, Vec<ControlId>> = HashMap::new();
+		for (id, control) in &self.controls {
+			children.entry(control.parent()).or_default().push(*id);
+		}
+		for ids in children.values_mut() {
+			ids.sort_by_key(|id| (self.controls[id].height, *id));
+		}
+		let mut ids = vec![];
+		let mut stack: Vec<ControlId> = children.get(&None).cloned().unwrap_or_default();
+		stack.reverse();
+		while let Some(id) = stack.pop() {
+			if !self.controls[&id].visible {
+				continue;
+			}
+			ids.push(id);
+			if let Some(child_ids) = children.get(&Some(id)) {
+				stack.extend(child_ids.iter().rev());
+			}
+		}
+		ids
+	}
+
+	/// Returns the IDs of all of the descendants of a control.
+	fn descendants(&self, id: ControlId) -> Vec<ControlId> {
+		let mut ids = vec![];
+		let mut stack = vec![id];
+		while let Some(id) = stack.pop() {
+			for child in self.sorted_children(Some(id)) {
+				ids.push(child);
+				stack.push(child);
+			}
+		}
+		ids
+	}
+
+	/// Returns the bounds of a control relative to the top-left
+	/// corner of the GUI (rather than its parent).
+	pub fn get_absolute_rectangle(&self, id: &ControlId) -> Option<Rectangle> {
+		let control = self.controls.get(id)?;
+		let mut rectangle = control.rectangle;
+		let mut parent = control.parent();
+		while let Some(parent_id) = parent {
+			let parent_control = &self.controls[&parent_id];
+			rectangle.x += parent_control.rectangle.x;
+			rectangle.y += parent_control.rectangle.y;
+			parent = parent_control.parent();
+		}
+		Some(rectangle)
+	}
+
+	/// Returns `true` if the control and all of its ancestors
+	/// are visible.
+	pub fn is_visible(&self, id: &ControlId) -> bool {
+		let mut current = Some(*id);
+		while let Some(id) = current {
+			match self.controls.get(&id) {
+				Some(control) if control.visible => current = control.parent(),
+				_ => return false,
+			}
+		}
+		true
 	}
 
 	/// Returns a reference to the control with the given ID.
@@ -41,18 +205,72 @@ impl Controls {
 	}
 
 	/// Returns a mutable reference to the control with the given ID.
+	///
+	/// The control (and its descendants, in case the control is
+	/// moved or hidden) will be redrawn on the next frame.
 	pub fn get_mut(&mut self, id: &ControlId) -> Option<&mut Control> {
+		self.invalidate_subtree(*id);
+		self.generation = self.generation.wrapping_add(1);
 		self.controls.get_mut(id)
 	}
+
+	/// Marks a control as needing to be redrawn on the next frame.
+	///
+	/// Behaviors that keep their own state (rather than storing it
+	/// in the control) should call this when that state changes
+	/// the way the control looks.
+	pub fn request_redraw(&mut self, id: &ControlId) {
+		self.invalidate(*id);
+	}
+
+	/// Starts an animation for a control, replacing the control's
+	/// animation with the same key (if there is one). Returns `false`
+	/// if there was no control with the given ID.
+	///
+	/// Tweens and springs of any `Animatable` type (like numbers,
+	/// colors, points, and rectangles) can be started. The GUI advances
+	/// the animation every frame and redraws the control until the
+	/// animation finishes. Behaviors can read the animation's value when
+	/// drawing with `Control::animation_value`.
+	pub fn start_animation<T: Animatable + 'static>(
+		&mut self,
+		id: &ControlId,
+		key: &'static str,
+		animation: impl ValueAnimation<T> + 'static,
+	) -> bool {
+		match self.controls.get_mut(id) {
+			Some(control) => {
+				control.start_animation(key, Box::new(BoxedValueAnimation(Box::new(animation))))
+			}
+			None => return false,
+		}
+		self.invalidate(*id);
+		true
+	}
 }
 
 /// A unqiue identifier for a font.
 pub type FontId = usize;
 
+/// A unqiue identifier for an image.
+pub type ImageId = usize;
+
+/// A unqiue identifier for a filmstrip.
+pub type FilmstripId = usize;
+
+/// A unqiue identifier for an SVG.
+pub type SvgId = usize;
+
 /// A collection of resources.
 pub struct Resources {
 	fonts: HashMap<FontId, Font<'static>>,
 	next_font_id: FontId,
+	images: HashMap<ImageId, Image>,
+	next_image_id: ImageId,
+	filmstrips: HashMap<FilmstripId, Filmstrip>,
+	next_filmstrip_id: FilmstripId,
+	svgs: HashMap<SvgId, Svg>,
+	next_svg_id: SvgId,
 }
 
 impl Resources {
@@ -60,6 +278,12 @@ impl Resources {
 		Self {
 			fonts: HashMap::new(),
 			next_font_id: 0,
+			images: HashMap::new(),
+			next_image_id: 0,
+			filmstrips: HashMap::new(),
+			next_filmstrip_id: 0,
+			svgs: HashMap::new(),
+			next_svg_id: 0,
 		}
 	}
 
@@ -78,22 +302,171 @@ impl Resources {
 	pub fn get_font(&self, id: FontId) -> Option<&Font> {
 		self.fonts.get(&id)
 	}
+
+	/// Loads an image from the contents of a PNG file.
+	///
+	/// Images wider or taller than `MAX_IMAGE_SIZE` are rejected.
+	pub fn load_image(&mut self, image_data: &[u8]) -> Result<ImageId, InvalidImageError> {
+		let image = Image::from_png(image_data)?;
+		if !image.fits_max_size() {
+			return Err(InvalidImageError);
+		}
+		Ok(self.add_image(image))
+	}
+
+	fn add_image(&mut self, image: Image) -> ImageId {
+		let id = self.next_image_id;
+		self.images.insert(id, image);
+		self.next_image_id += 1;
+		id
+	}
+
+	/// Gets a reference to a previously loaded image.
+	pub fn get_image(&self, id: ImageId) -> Option<&Image> {
+		self.images.get(&id)
+	}
+
+	/// Loads a filmstrip from the contents of a PNG file that contains
+	/// `frame_count` equally sized frames.
+	///
+	/// The image's width (for horizontal filmstrips) or height (for
+	/// vertical filmstrips) must be a multiple of `frame_count`, and
+	/// each frame must fit within `MAX_IMAGE_SIZE`. The frames are
+	/// also available as regular images using the filmstrip's `image_ids`.
+	pub fn load_filmstrip(
+		&mut self,
+		image_data: &[u8],
+		frame_count: usize,
+		orientation: FilmstripOrientation,
+	) -> Result<FilmstripId, InvalidImageError> {
+		let image = Image::from_png(image_data)?;
+		let (mut filmstrip, images) = Filmstrip::split(image, frame_count, orientation)?;
+		filmstrip.image_ids = images
+			.into_iter()
+			.map(|image| self.add_image(image))
+			.collect();
+		let id = self.next_filmstrip_id;
+		self.filmstrips.insert(id, filmstrip);
+		self.next_filmstrip_id += 1;
+		Ok(id)
+	}
+
+	/// Gets a reference to a previously loaded filmstrip.
+	pub fn get_filmstrip(&self, id: FilmstripId) -> Option<&Filmstrip> {
+		self.filmstrips.get(&id)
+	}
+
+	/// Loads a vector image from the contents of an SVG file.
+	///
+	/// Only a subset of SVG is supported (see `Svg` for details).
+	pub fn load_svg(&mut self, svg_data: &str) -> Result<SvgId, InvalidSvgError> {
+		let svg = Svg::parse(svg_data)?;
+		let id = self.next_svg_id;
+		self.svgs.insert(id, svg);
+		self.next_svg_id += 1;
+		Ok(id)
+	}
+
+	/// Gets a reference to a previously loaded SVG.
+	pub fn get_svg(&self, id: SvgId) -> Option<&Svg> {
+		self.svgs.get(&id)
+	}
 }
 
 /// A list of events to send to the audio thread.
+///
+/// The queue keeps track of which parameter gestures are in progress
+/// so that every `BeginParameterGesture` event is followed by exactly
+/// one `EndParameterGesture` event for the same parameter.
 pub struct EventQueue<CustomEvent> {
 	events: Vec<Event<CustomEvent>>,
+	current_control: Option<ControlId>,
+	open_gestures: HashMap<i32, Option<ControlId>>,
+	coalesce_parameters: bool,
 }
 
 impl<CustomEvent> EventQueue<CustomEvent> {
 	fn new() -> Self {
-		Self { events: vec![] }
+		Self {
+			events: vec![],
+			current_control: None,
+			open_gestures: HashMap::new(),
+			coalesce_parameters: false,
+		}
 	}
 
 	/// Pushes an event to the queue.
+	///
+	/// Beginning a gesture for a parameter that's already being
+	/// changed, or ending a gesture that was never started, does nothing.
+	/// If parameter coalescing is enabled, a `SetParameter` event may
+	/// update the value of an event that's already queued instead.
 	pub fn push(&mut self, event: Event<CustomEvent>) {
+		match event {
+			Event::BeginParameterGesture(index) => {
+				if self.open_gestures.contains_key(&index) {
+					return;
+				}
+				self.open_gestures.insert(index, self.current_control);
+			}
+			Event::EndParameterGesture(index) if self.open_gestures.remove(&index).is_none() => {
+				return;
+			}
+			Event::SetParameter(index, value)
+				if self.coalesce_parameters && self.replace_parameter_value(index, value) =>
+			{
+				return;
+			}
+			_ => {}
+		}
 		self.events.push(event);
 	}
+
+	/// Looks for a queued `SetParameter` event for the given parameter
+	/// that can be updated with a new value instead of pushing another
+	/// event. Returns `true` if an event was updated.
+	///
+	/// Only `SetParameter` events for other parameters are skipped over,
+	/// so the new value never moves before or after any other kind of event.
+	fn replace_parameter_value(&mut self, index: i32, value: f32) -> bool {
+		for event in self.events.iter_mut().rev() {
+			match event {
+				Event::SetParameter(queued_index, queued_value) if *queued_index == index => {
+					*queued_value = value;
+					return true;
+				}
+				Event::SetParameter(_, _) => {}
+				_ => return false,
+			}
+		}
+		false
+	}
+
+	/// Ends all of the gestures that were started by the control
+	/// with the given ID.
+	fn end_gestures(&mut self, id: ControlId) {
+		let mut indices: Vec<i32> = self
+			.open_gestures
+			.iter()
+			.filter(|(_, control)| **control == Some(id))
+			.map(|(index, _)| *index)
+			.collect();
+		indices.sort_unstable();
+		for index in indices {
+			self.push(Event::EndParameterGesture(index));
+		}
+	}
+}
+
+/// A mouse press that might be followed by more presses
+/// as part of a double click.
+#[derive(Copy, Clone)]
+struct PreviousPress {
+	control_id: ControlId,
+	mouse_button: MouseButton,
+	time: f64,
+	position: Point,
+	click_count: u32,
 }
 
 /// A collection of controls and associated behaviors.
@@ -105,10 +478,43 @@ pub struct Gui<CustomEvent> {
 	/// The list of controls contained in the GUI.
 	pub controls: Controls,
 	pub resources: Resources,
+	/// Information about the plugin's parameters.
+	pub parameters: Parameters,
+	/// The maximum number of seconds between two clicks
+	/// for them to count as a double click.
+	pub double_click_interval: f64,
+	/// The maximum distance the mouse can move between two clicks
+	/// for them to count as a double click.
+	pub double_click_distance: f32,
 	behaviors: HashMap<ControlId, Vec<Box<dyn Behavior<CustomEvent>>>>,
 	hovered_control: Option<ControlId>,
 	held_control: EnumMap<MouseButton, Option<ControlId>>,
+	focused_control: Option<ControlId>,
+	previous_press: Option<PreviousPress>,
+	mouse_position: Point,
+	click_count: EnumMap<MouseButton, u32>,
 	event_queue: EventQueue<CustomEvent>,
+	audio_connection: Option<GuiConnection<CustomEvent>>,
+	meters: Meters,
+	full_redraw: bool,
+	draw_cache: HashMap<ControlId, Vec<DrawOperation>>,
+	draw_order: Vec<ControlId>,
+	/// The generation of the controls `draw_order` was worked out for.
+	draw_order_generation: Option<usize>,
+	/// The state that was last saved to the controls by
+	/// `update_control_state`.
+	synced_control_state: Option<SyncedControlState>,
+	clip_controls: bool,
+}
+
+/// Everything `Gui::update_control_state` saves to the controls,
+/// so it can skip the work when nothing has changed.
+#[derive(Clone, Copy, PartialEq)]
+struct SyncedControlState {
+	generation: usize,
+	hovered_control: Option<ControlId>,
+	held_control: EnumMap<MouseButton, Option<ControlId>>,
+	focused_control: Option<ControlId>,
 }
 
 impl<CustomEvent> Gui<CustomEvent>
@@ -120,6 +526,9 @@ where
 		Self {
 			controls: Controls::new(),
 			resources: Resources::new(),
+			parameters: Parameters::new(),
+			double_click_interval: 0.5,
+			double_click_distance: 4.0,
 			behaviors: HashMap::new(),
 			hovered_control: None,
 			held_control: enum_map! {
@@ -127,20 +536,76 @@ where
 				MouseButton::Middle => None,
 				MouseButton::Right => None,
 			},
+			focused_control: None,
+			previous_press: None,
+			mouse_position: Point::new(0.0, 0.0),
+			click_count: enum_map! {
+				MouseButton::Left => 0,
+				MouseButton::Middle => 0,
+				MouseButton::Right => 0,
+			},
 			event_queue: EventQueue::new(),
+			audio_connection: None,
+			meters: Meters::new(MeterLayout::new()).0,
+			full_redraw: true,
+			draw_cache: HashMap::new(),
+			draw_order: vec![],
+			draw_order_generation: None,
+			synced_control_state: None,
+			clip_controls: false,
 		}
 	}
 
 	/// Adds a control to the GUI and attaches the given
 	/// behaviors to it.
+	///
+	/// Returns `None` (and drops the behaviors) if the control's
+	/// parent hasn't been added to the GUI.
 	pub fn add_control(
 		&mut self,
 		settings: ControlSettings,
 		behaviors: Vec<Box<dyn Behavior<CustomEvent>>>,
-	) -> ControlId {
-		let id = self.controls.add(&settings);
+	) -> Option<ControlId> {
+		let id = self.controls.add(&settings)?;
 		self.behaviors.insert(id, behaviors);
-		id
+		self.emit(Event::Added(id), Some(id));
+		Some(id)
+	}
+
+	/// Removes a control and its behaviors from the GUI.
+	///
+	/// All of the control's descendants are removed as well.
+	/// The behaviors of each removed control receive a `Removed`
+	/// event before they're dropped. Returns `false` if there was
+	/// no control with the given ID.
+	pub fn remove_control(&mut self, id: ControlId) -> bool {
+		if self.controls.get(&id).is_none() {
+			return false;
+		}
+		// remove the children before their parents
+		let mut ids = vec![id];
+		ids.extend(self.controls.descendants(id));
+		for id in ids.into_iter().rev() {
+			self.emit(Event::Removed(id), Some(id));
+			self.event_queue.end_gestures(id);
+			self.controls.invalidate(id);
+			self.controls.remove(&id);
+			self.behaviors.remove(&id);
+			self.draw_cache.remove(&id);
+			// forget about the control if it was hovered, held, or focused
+			if self.hovered_control == Some(id) {
+				self.hovered_control = None;
+			}
+			if self.focused_control == Some(id) {
+				self.focused_control = None;
+			}
+			for (_, held) in &mut self.held_control {
+				if *held == Some(id) {
+					*held = None;
+				}
+			}
+		}
+		true
 	}
 
 	/// Emits an event to the behaviors in the GUI.
@@ -148,30 +613,147 @@ where
 	/// If a control ID is specified, the event will only be emitted to
 	/// behaviors attached to the control with that ID. Otherwise, all
 	/// behaviors will receive the event.
+	///
+	/// Emitting a `SetParameter` or `ResetParameter` event marks every
+	/// control as dirty, since any control might display the parameter.
 	pub fn emit(&mut self, event: Event<CustomEvent>, control_id: Option<ControlId>) {
+		if let Event::SetParameter(_, _) | Event::ResetParameter(_) = event {
+			self.controls.invalidate_all();
+		}
 		if let Some(id) = control_id {
 			if let Some(behaviors) = self.behaviors.get_mut(&id) {
+				self.event_queue.current_control = Some(id);
 				for behavior in behaviors {
 					behavior.on(
 						event,
 						&mut self.controls,
 						&self.resources,
+						&self.parameters,
 						&mut self.event_queue,
 					);
 				}
 			}
 		} else {
-			for (_, behaviors) in &mut self.behaviors {
+			for (id, behaviors) in &mut self.behaviors {
+				self.event_queue.current_control = Some(*id);
 				for behavior in behaviors {
 					behavior.on(
 						event,
 						&mut self.controls,
 						&self.resources,
+						&self.parameters,
 						&mut self.event_queue,
 					);
 				}
 			}
 		}
+		self.event_queue.current_control = None;
+	}
+
+	/// Advances the GUI by one frame, emitting an `Update` event
+	/// to every behavior that wants updates or is animating, and
+	/// advancing the animations started with `Controls::start_animation`.
+	///
+	/// Controls with animating behaviors or running animations are marked
+	/// as dirty, as are all controls if the audio thread has published
+	/// new meter values. Controls that have been hidden stop being
+	/// hovered, held, or focused.
+	///
+	/// `dt` is the number of seconds since the last frame.
+	pub fn update(&mut self, dt: f32) {
+		self.forget_hidden_controls();
+		if self.meters.refresh() {
+			self.controls.invalidate_all();
+		}
+		// like behaviors, controls are redrawn one more time after
+		// their animations finish
+		let animating: Vec<ControlId> = self
+			.controls
+			.controls
+			.iter_mut()
+			.filter_map(|(id, control)| control.update_animations(dt).then_some(*id))
+			.collect();
+		for id in animating {
+			self.controls.invalidate(id);
+		}
+		for (id, behaviors) in &mut self.behaviors {
+			self.event_queue.current_control = Some(*id);
+			let mut animating = false;
+			for behavior in behaviors {
+				// keep redrawing for one more frame after an animation
+				// finishes so its final value is shown
+				animating |= behavior.is_animating();
+				if behavior.wants_updates() || behavior.is_animating() {
+					behavior.on(
+						Event::Update(dt),
+						&mut self.controls,
+						&self.resources,
+						&self.parameters,
+						&mut self.event_queue,
+					);
+				}
+			}
+			if animating {
+				self.controls.request_redraw(id);
+			}
+		}
+		self.event_queue.current_control = None;
+	}
+
+	/// Returns `true` if any behavior or control has an animation
+	/// in progress.
+	///
+	/// Backends can use this to keep redrawing while something is
+	/// animating and stop once everything has come to rest.
+	pub fn is_animating(&self) -> bool {
+		self.behaviors
+			.values()
+			.flatten()
+			.any(|behavior| behavior.is_animating())
+			|| self
+				.controls
+				.controls
+				.values()
+				.any(|control| control.is_animating())
+	}
+
+	/// Returns `true` if anything has changed since the GUI
+	/// was last drawn.
+	///
+	/// Backends can skip drawing (and presenting) frames when
+	/// nothing needs to be redrawn.
+	pub fn needs_redraw(&self) -> bool {
+		self.full_redraw
+			|| !self.controls.dirty.is_empty()
+			|| !self.controls.invalidated_regions.is_empty()
+			|| self.is_animating()
+	}
+
+	/// Returns the areas of the GUI that need to be redrawn, or `None`
+	/// if the whole GUI needs to be redrawn.
+	///
+	/// This includes the current bounds of each dirty control as well
+	/// as the areas dirty controls covered before they were moved,
+	/// hidden, or removed. The areas may overlap.
+	pub fn invalidated_regions(&self) -> Option<Vec<Rectangle>> {
+		if self.full_redraw {
+			return None;
+		}
+		let mut regions = self.controls.invalidated_regions.clone();
+		for id in &self.controls.dirty {
+			if self.controls.is_visible(id) {
+				regions.push(self.controls.get_absolute_rectangle(id).unwrap());
+			}
+		}
+		Some(regions)
+	}
+
+	/// Asks for the whole GUI to be redrawn on the next frame.
+	///
+	/// Backends should call this when the window is resized
+	/// or its contents are otherwise lost.
+	pub fn request_full_redraw(&mut self) {
+		self.full_redraw = true;
 	}
 
 	/// Flushes the event queue and returns a list of all of the event
@@ -180,40 +762,203 @@ where
 		self.event_queue.events.drain(..).collect()
 	}
 
+	/// Sets up the meters the audio thread will publish values to
+	/// and returns the audio thread's end of them.
+	///
+	/// Behaviors can read the latest meter values when they're drawn.
+	/// Setting up the meters again replaces the previous meters.
+	pub fn set_meter_layout(&mut self, layout: MeterLayout) -> MeterPublisher {
+		let (meters, publisher) = Meters::new(layout);
+		self.meters = meters;
+		publisher
+	}
+
+	/// Sets whether parameter changes should be coalesced.
+	///
+	/// When enabled, pushing a `SetParameter` event replaces the value of
+	/// the last queued `SetParameter` event for the same parameter, as long
+	/// as no other kind of event (like a gesture or custom event) was pushed
+	/// in between. This keeps a single drag from flooding the audio thread
+	/// with parameter changes.
+	pub fn set_parameter_coalescing(&mut self, enabled: bool) {
+		self.event_queue.coalesce_parameters = enabled;
+	}
+
+	/// Sets whether each control's drawing should be clipped
+	/// to the control's bounds.
+	///
+	/// This is disabled by default, so behaviors can draw things
+	/// like labels and shadows outside of their controls.
+	pub fn set_control_clipping(&mut self, enabled: bool) {
+		if enabled != self.clip_controls {
+			self.clip_controls = enabled;
+			self.full_redraw = true;
+		}
+	}
+
+	/// Creates a lock-free connection between the GUI and the audio
+	/// thread and returns the audio thread's end of it.
+	///
+	/// Once connected, `sync_audio` should be called regularly
+	/// (usually once per frame) to exchange events with the audio thread.
+	/// Connecting again replaces the previous connection.
+	pub fn connect_audio(&mut self, settings: AudioBridgeSettings) -> AudioHandle<CustomEvent> {
+		let (connection, handle) = crate::audio::connect(settings);
+		self.audio_connection = Some(connection);
+		handle
+	}
+
+	/// Sends the queued events to the audio thread and emits the events
+	/// the audio thread sent to the behaviors in the GUI.
+	///
+	/// Parameter gesture events are never dropped, even if the queue
+	/// to the audio thread is full. Instead, they're sent again the next
+	/// time this is called.
+	///
+	/// Does nothing if the GUI isn't connected to the audio thread.
+	pub fn sync_audio(&mut self) {
+		if let Some(connection) = &mut self.audio_connection {
+			connection.send_unsent();
+			for event in self.event_queue.events.drain(..) {
+				connection.send(event);
+			}
+		}
+		while let Some(event) = self
+			.audio_connection
+			.as_mut()
+			.and_then(|connection| connection.from_audio.receive())
+		{
+			self.emit(event, None);
+		}
+	}
+
+	/// Gives keyboard focus to the control with the given ID,
+	/// or removes focus from all controls if `id` is `None`.
+	///
+	/// Returns `false` (and leaves the focus where it was) if there's
+	/// no control with the given ID or the control is hidden or
+	/// isn't focusable.
+	pub fn focus(&mut self, id: Option<ControlId>) -> bool {
+		if let Some(id) = id {
+			let can_focus = match self.controls.get(&id) {
+				Some(control) => control.focusable && self.controls.is_visible(&id),
+				None => false,
+			};
+			if !can_focus {
+				return false;
+			}
+		}
+		if id == self.focused_control {
+			return true;
+		}
+		let previous_focused_control = self.focused_control;
+		self.focused_control = id;
+		self.update_control_state();
+		if let Some(id) = previous_focused_control {
+			self.emit(Event::Blur(id), Some(id));
+		}
+		if let Some(id) = id {
+			self.emit(Event::Focus(id), Some(id));
+		}
+		true
+	}
+
+	/// Moves the keyboard focus to the next (or previous) visible,
+	/// focusable control in drawing order.
+	fn cycle_focus(&mut self, backwards: bool) {
+		let mut ids: Vec<ControlId> = self
+			.controls
+			.draw_order()
+			.into_iter()
+			.filter(|id| self.controls.get(id).unwrap().focusable)
+			.collect();
+		if ids.is_empty() {
+			return;
+		}
+		if backwards {
+			ids.reverse();
+		}
+		let next = match self
+			.focused_control
+			.and_then(|focused| ids.iter().position(|id| *id == focused))
+		{
+			Some(index) => ids[(index + 1) % ids.len()],
+			None => ids[0],
+		};
+		self.focus(Some(next));
+	}
+
 	/* it makes the most sense to store the control hovered/held state
 	in the Gui struct, since only one control will be hovered/held at
 	a time. however, it's also nice for the Behaviors to be able to access
 	the hovered/held state via the Control structs, so we save that info
 	there as well. but the state in the Gui struct is the "canonical" one. */
 	fn update_control_state(&mut self) {
+		let state = SyncedControlState {
+			generation: self.controls.generation,
+			hovered_control: self.hovered_control,
+			held_control: self.held_control,
+			focused_control: self.focused_control,
+		};
+		if self.synced_control_state == Some(state) {
+			return;
+		}
+		self.synced_control_state = Some(state);
+		let absolute_rectangles: HashMap<ControlId, Rectangle> = self
+			.controls
+			.controls
+			.keys()
+			.map(|id| (*id, self.controls.get_absolute_rectangle(id).unwrap()))
+			.collect();
 		for (id, control) in &mut self.controls.controls {
-			control.is_hovered = self.hovered_control == Some(*id);
+			let is_hovered = self.hovered_control == Some(*id);
+			let is_focused = self.focused_control == Some(*id);
+			let mut changed = control.is_hovered != is_hovered || control.is_focused != is_focused;
+			control.absolute_rectangle = absolute_rectangles[id];
+			control.is_hovered = is_hovered;
+			control.is_focused = is_focused;
 			for (mouse_button, held) in &mut control.is_held {
-				*held = self.held_control[mouse_button] == Some(*id);
+				let is_held = self.held_control[mouse_button] == Some(*id);
+				changed |= *held != is_held;
+				*held = is_held;
+			}
+			// controls usually look different when they're hovered,
+			// held, or focused, so they need to be redrawn
+			if changed {
+				self.controls.dirty.insert(*id);
 			}
 		}
 	}
 
+	/// Returns the ID of the topmost visible control that contains
+	/// the given point.
+	fn get_control_at(&self, point: Point) -> Option<ControlId> {
+		self.controls.draw_order().into_iter().rev().find(|id| {
+			self.controls
+				.get_absolute_rectangle(id)
+				.unwrap()
+				.contains_point(point)
+		})
+	}
+
 	/// Tells the GUI about a mouse movement.
-	pub fn on_mouse_move(&mut self, x: f32, y: f32, dx: f32, dy: f32) {
+	pub fn on_mouse_move(&mut self, x: f32, y: f32, dx: f32, dy: f32, modifiers: Modifiers) {
+		self.forget_hidden_controls();
+		self.mouse_position = Point::new(x, y);
 		let previous_hovered_control = self.hovered_control;
-		// get the first hovered control
-		self.hovered_control = None;
-		for (id, control) in &self.controls.controls {
-			if control.rectangle.contains_point(Point::new(x, y)) {
-				self.hovered_control = Some(*id);
-				break;
-			}
-		}
+		self.hovered_control = self.get_control_at(self.mouse_position);
 		// save the hovered state to the controls
 		self.update_control_state();
 		// emit hover/unhover events
 		if self.hovered_control != previous_hovered_control {
 			if let Some(id) = self.hovered_control {
-				let control = self.controls.get(&id).unwrap();
-				let relative_x = x - control.rectangle.x;
-				let relative_y = y - control.rectangle.y;
-				self.emit(Event::Hover(id, relative_x, relative_y), Some(id));
+				let rectangle = self.controls.get_absolute_rectangle(&id).unwrap();
+				let relative_x = x - rectangle.x;
+				let relative_y = y - rectangle.y;
+				self.emit(
+					Event::Hover(id, relative_x, relative_y, modifiers),
+					Some(id),
+				);
 			}
 			if let Some(id) = previous_hovered_control {
 				self.emit(Event::Unhover(id), Some(id));
@@ -223,64 +968,334 @@ where
 		let held_control = self.held_control;
 		for (mouse_button, held) in &held_control {
 			if let Some(id) = held {
-				let control = self.controls.get(&id).unwrap();
-				let relative_x = x - control.rectangle.x;
-				let relative_y = y - control.rectangle.y;
+				let rectangle = self.controls.get_absolute_rectangle(id).unwrap();
+				let relative_x = x - rectangle.x;
+				let relative_y = y - rectangle.y;
 				self.emit(
-					Event::Drag(*id, mouse_button, relative_x, relative_y, dx, dy),
+					Event::Drag(*id, mouse_button, relative_x, relative_y, dx, dy, modifiers),
 					Some(*id),
 				);
 			}
 		}
 	}
 
+	/// Counts how many times a control has been pressed in quick
+	/// succession, including the current press.
+	fn count_clicks(
+		&mut self,
+		id: ControlId,
+		mouse_button: MouseButton,
+		position: Point,
+		time: f64,
+	) -> u32 {
+		let click_count = match self.previous_press {
+			Some(previous)
+				if previous.control_id == id
+					&& previous.mouse_button == mouse_button
+					&& time - previous.time <= self.double_click_interval
+					&& (position.x - previous.position.x)
+						.hypot(position.y - previous.position.y)
+						<= self.double_click_distance =>
+			{
+				previous.click_count + 1
+			}
+			_ => 1,
+		};
+		self.previous_press = Some(PreviousPress {
+			control_id: id,
+			mouse_button,
+			time,
+			position,
+			click_count,
+		});
+		click_count
+	}
+
+	/// Stops the next press of a control from counting as another
+	/// click in a series after a press that didn't end in a click.
+	fn forget_previous_press(&mut self, id: ControlId, mouse_button: MouseButton) {
+		if let Some(previous) = self.previous_press {
+			if previous.control_id == id && previous.mouse_button == mouse_button {
+				self.previous_press = None;
+			}
+		}
+	}
+
 	/// Tells the GUI about a mouse button press.
-	pub fn on_mouse_down(&mut self, mouse_button: MouseButton, x: f32, y: f32) {
+	///
+	/// `time` is the time of the press in seconds (measured from
+	/// any fixed point in time), which is used to detect double clicks.
+	pub fn on_mouse_down(
+		&mut self,
+		mouse_button: MouseButton,
+		x: f32,
+		y: f32,
+		modifiers: Modifiers,
+		time: f64,
+	) {
+		self.forget_hidden_controls();
+		// clicking a control focuses it, and clicking anywhere else
+		// removes focus
+		let focus_target = self
+			.hovered_control
+			.filter(|id| self.controls.get(id).unwrap().focusable);
+		self.focus(focus_target);
 		if let Some(id) = self.hovered_control {
 			// update the held state
 			self.held_control[mouse_button] = Some(id);
 			self.update_control_state();
+			self.click_count[mouse_button] =
+				self.count_clicks(id, mouse_button, Point::new(x, y), time);
 			// emit the press event
-			let control = self.controls.get(&id).unwrap();
-			let relative_x = x - control.rectangle.x;
-			let relative_y = y - control.rectangle.y;
+			let rectangle = self.controls.get_absolute_rectangle(&id).unwrap();
+			let relative_x = x - rectangle.x;
+			let relative_y = y - rectangle.y;
 			self.emit(
-				Event::Press(id, mouse_button, relative_x, relative_y),
+				Event::Press(id, mouse_button, relative_x, relative_y, modifiers),
 				Some(id),
 			);
 		}
 	}
 
 	/// Tells the GUI about a mouse button release.
-	pub fn on_mouse_up(&mut self, mouse_button: MouseButton, x: f32, y: f32) {
+	pub fn on_mouse_up(&mut self, mouse_button: MouseButton, x: f32, y: f32, modifiers: Modifiers) {
+		self.forget_hidden_controls();
 		let previous_held_control = self.held_control;
 		if let Some(id) = previous_held_control[mouse_button] {
 			// update the held state
 			self.held_control[mouse_button] = None;
 			self.update_control_state();
 			// emit release/click events
-			let control = self.controls.get(&id).unwrap();
-			let relative_x = x - control.rectangle.x;
-			let relative_y = y - control.rectangle.y;
+			let rectangle = self.controls.get_absolute_rectangle(&id).unwrap();
+			let relative_x = x - rectangle.x;
+			let relative_y = y - rectangle.y;
 			self.emit(
-				Event::Release(id, mouse_button, relative_x, relative_y),
+				Event::Release(id, mouse_button, relative_x, relative_y, modifiers),
 				Some(id),
 			);
 			if self.hovered_control == Some(id) {
+				let click_count = self.click_count[mouse_button];
 				self.emit(
-					Event::Click(id, mouse_button, relative_x, relative_y),
+					Event::Click(
+						id,
+						mouse_button,
+						relative_x,
+						relative_y,
+						modifiers,
+						click_count,
+					),
 					Some(id),
 				);
+				if click_count == 2 {
+					self.emit(
+						Event::DoubleClick(id, mouse_button, relative_x, relative_y, modifiers),
+						Some(id),
+					);
+				}
+			} else {
+				self.forget_previous_press(id, mouse_button);
+			}
+			self.end_gestures_if_released(id);
+		}
+	}
+
+	/// Releases all of the controls that are held down, as if
+	/// the mouse buttons were released outside of the controls.
+	///
+	/// Backends should call this when the window loses focus,
+	/// since the GUI may never be told that the mouse buttons
+	/// were released.
+	pub fn cancel_mouse_input(&mut self) {
+		let previous_held_control = self.held_control;
+		for (mouse_button, held) in &previous_held_control {
+			if let Some(id) = *held {
+				self.release(id, mouse_button);
+			}
+		}
+	}
+
+	/// Releases a held control without clicking it.
+	fn release(&mut self, id: ControlId, mouse_button: MouseButton) {
+		self.held_control[mouse_button] = None;
+		self.update_control_state();
+		self.forget_previous_press(id, mouse_button);
+		let rectangle = self.controls.get_absolute_rectangle(&id).unwrap();
+		let relative_x = self.mouse_position.x - rectangle.x;
+		let relative_y = self.mouse_position.y - rectangle.y;
+		self.emit(
+			Event::Release(id, mouse_button, relative_x, relative_y, Modifiers::new()),
+			Some(id),
+		);
+		self.end_gestures_if_released(id);
+	}
+
+	/// Unhovers, releases, and removes focus from controls that have
+	/// been hidden (or whose ancestors have been hidden), since hidden
+	/// controls can't be interacted with.
+	fn forget_hidden_controls(&mut self) {
+		if let Some(id) = self.hovered_control {
+			if !self.controls.is_visible(&id) {
+				self.hovered_control = None;
+				self.update_control_state();
+				self.emit(Event::Unhover(id), Some(id));
+			}
+		}
+		let held_control = self.held_control;
+		for (mouse_button, held) in &held_control {
+			if let Some(id) = *held {
+				if !self.controls.is_visible(&id) {
+					self.release(id, mouse_button);
+				}
+			}
+		}
+		if let Some(id) = self.focused_control {
+			if !self.controls.is_visible(&id) {
+				self.focus(None);
 			}
 		}
 	}
 
+	/// Ends the parameter gestures started by a control once
+	/// no mouse buttons are holding it down.
+	///
+	/// This is called after every mouse release and after every scroll
+	/// or key event, since those don't have a release of their own.
+	fn end_gestures_if_released(&mut self, id: ControlId) {
+		if self.held_control.values().all(|held| *held != Some(id)) {
+			self.event_queue.end_gestures(id);
+		}
+	}
+
+	/// Tells the GUI about a mouse wheel or trackpad scroll.
+	///
+	/// The scroll event is sent to the topmost control
+	/// under the given mouse position.
+	pub fn on_scroll(
+		&mut self,
+		x: f32,
+		y: f32,
+		dx: f32,
+		dy: f32,
+		unit: ScrollUnit,
+		modifiers: Modifiers,
+	) {
+		self.forget_hidden_controls();
+		if let Some(id) = self.get_control_at(Point::new(x, y)) {
+			self.emit(Event::Scroll(id, dx, dy, unit, modifiers), Some(id));
+			self.end_gestures_if_released(id);
+		}
+	}
+
+	/// Tells the GUI about a key press.
+	///
+	/// Pressing tab moves the focus to the next focusable control,
+	/// and pressing shift+tab moves it to the previous one. Other key
+	/// presses are sent to the focused control.
+	pub fn on_key_down(&mut self, key: Key, modifiers: Modifiers) {
+		self.forget_hidden_controls();
+		if key == Key::Tab {
+			self.cycle_focus(modifiers.contains(Modifier::Shift));
+			return;
+		}
+		if let Some(id) = self.focused_control {
+			self.emit(Event::KeyDown(id, key, modifiers), Some(id));
+			self.end_gestures_if_released(id);
+		}
+	}
+
+	/// Tells the GUI about a key release.
+	pub fn on_key_up(&mut self, key: Key, modifiers: Modifiers) {
+		self.forget_hidden_controls();
+		if key == Key::Tab {
+			return;
+		}
+		if let Some(id) = self.focused_control {
+			self.emit(Event::KeyUp(id, key, modifiers), Some(id));
+			self.end_gestures_if_released(id);
+		}
+	}
+
+	/// Tells the GUI about a character of text that was typed.
+	pub fn on_text_input(&mut self, character: char) {
+		self.forget_hidden_controls();
+		if let Some(id) = self.focused_control {
+			self.emit(Event::TextInput(id, character), Some(id));
+			self.end_gestures_if_released(id);
+		}
+	}
+
 	/// Draws the GUI to a canvas.
-	pub fn draw(&self, canvas: &mut Canvas) {
-		for (id, control) in &self.controls.controls {
-			for behavior in &self.behaviors[id] {
-				behavior.draw(control, &self.resources, canvas);
+	///
+	/// Controls are drawn from bottom to top, so controls with
+	/// a greater height will be drawn over controls with a lower height.
+	/// Children are always drawn over their parents, and hidden
+	/// controls (along with their descendants) aren't drawn at all.
+	///
+	/// The drawing operations for each control are cached, and the
+	/// control's behaviors are only asked to draw again once the control
+	/// is dirty (or one of its behaviors is animating). Drawing marks
+	/// every control as clean.
+	///
+	/// This copies every cached operation to the canvas. Backends can
+	/// avoid the copies by calling `update_draw_cache` and then
+	/// drawing the operations from `draw_operations` directly.
+	pub fn draw(&mut self, canvas: &mut Canvas) {
+		self.update_draw_cache();
+		canvas.operations.extend(self.draw_operations().cloned());
+	}
+
+	/// Asks the behaviors of every control that needs to be redrawn
+	/// to draw again and caches the results (see `draw`), then marks
+	/// every control as clean.
+	pub fn update_draw_cache(&mut self) {
+		self.update_control_state();
+		if self.meters.refresh() {
+			self.controls.invalidate_all();
+		}
+		// the draw order only changes when controls are added,
+		// removed, or changed
+		if self.draw_order_generation != Some(self.controls.generation) {
+			self.draw_order = self.controls.draw_order();
+			self.draw_order_generation = Some(self.controls.generation);
+		}
+		for id in &self.draw_order {
+			let behaviors = &self.behaviors[id];
+			let needs_redraw = self.full_redraw
+				|| self.controls.dirty.contains(id)
+				|| !self.draw_cache.contains_key(id)
+				|| behaviors.iter().any(|behavior| behavior.is_animating());
+			if needs_redraw {
+				let control = self.controls.get(id).unwrap();
+				let mut control_canvas = Canvas::new();
+				if self.clip_controls {
+					control_canvas.push_clip(control.absolute_rectangle);
+				}
+				for behavior in behaviors {
+					behavior.draw(
+						control,
+						&self.resources,
+						&self.parameters,
+						&self.meters,
+						&mut control_canvas,
+					);
+				}
+				// don't let one control's transforms or clip rectangles
+				// leak into the controls drawn after it
+				control_canvas.restore();
+				self.draw_cache.insert(*id, control_canvas.operations);
 			}
 		}
+		self.controls.clear_dirty();
+		self.full_redraw = false;
+	}
+
+	/// Returns the cached drawing operations of every visible control
+	/// (as of the last call to `update_draw_cache`) in the order they
+	/// should be drawn.
+	pub fn draw_operations(&self) -> impl Iterator<Item = &DrawOperation> + '_ {
+		self.draw_order
+			.iter()
+			.filter_map(move |id| self.draw_cache.get(id))
+			.flatten()
 	}
 }
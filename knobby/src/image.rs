@@ -0,0 +1,449 @@
+use crate::{error::InvalidImageError, geometry::Rectangle, gui::ImageId};
+
+/// The largest width or height (in pixels) an image can have.
+///
+/// Many graphics cards can't create textures any bigger than this.
+pub const MAX_IMAGE_SIZE: u32 = 8192;
+
+/// The most pixels a decoded image can have.
+///
+/// Filmstrips can be longer than `MAX_IMAGE_SIZE`, but this keeps
+/// a PNG with a huge size in its header from using up all of the memory.
+const MAX_PIXEL_COUNT: usize = MAX_IMAGE_SIZE as usize * MAX_IMAGE_SIZE as usize;
+
+/// A bitmap image.
+#[derive(Clone, Debug)]
+pub struct Image {
+	/// The width of the image in pixels.
+	pub width: u32,
+	/// The height of the image in pixels.
+	pub height: u32,
+	pixels: Vec<u8>,
+}
+
+impl Image {
+	/// Decodes a PNG image.
+	pub fn from_png(data: &[u8]) -> Result<Self, InvalidImageError> {
+		let mut decoder = png::Decoder::new(data);
+		// convert palettes, low bit depths, and transparency chunks to
+		// 8-bit grayscale or RGB(A), and 16-bit channels to 8-bit channels
+		decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+		let (info, mut reader) = decoder.read_info().map_err(|_| InvalidImageError)?;
+		// check the size before allocating anything for the pixels
+		let pixel_count = (info.width as usize)
+			.checked_mul(info.height as usize)
+			.filter(|pixel_count| *pixel_count <= MAX_PIXEL_COUNT)
+			.ok_or(InvalidImageError)?;
+		let mut buffer = vec![0; info.buffer_size()];
+		reader
+			.next_frame(&mut buffer)
+			.map_err(|_| InvalidImageError)?;
+		let mut pixels = Vec::with_capacity(pixel_count * 4);
+		match info.color_type {
+			png::ColorType::RGBA => pixels.extend_from_slice(&buffer),
+			png::ColorType::RGB => {
+				for pixel in buffer.chunks_exact(3) {
+					pixels.extend_from_slice(&[pixel[0], pixel[1], pixel[2], 255]);
+				}
+			}
+			png::ColorType::GrayscaleAlpha => {
+				for pixel in buffer.chunks_exact(2) {
+					pixels.extend_from_slice(&[pixel[0], pixel[0], pixel[0], pixel[1]]);
+				}
+			}
+			png::ColorType::Grayscale => {
+				for value in &buffer {
+					pixels.extend_from_slice(&[*value, *value, *value, 255]);
+				}
+			}
+			// indexed images are expanded to RGB(A)
+			png::ColorType::Indexed => return Err(InvalidImageError),
+		}
+		if pixels.len() < pixel_count * 4 {
+			return Err(InvalidImageError);
+		}
+		pixels.truncate(pixel_count * 4);
+		Ok(Self {
+			width: info.width,
+			height: info.height,
+			pixels,
+		})
+	}
+
+	/// Gets the color of each pixel as 8-bit RGBA values, row by row,
+	/// starting from the top-left corner.
+	pub fn pixels(&self) -> &[u8] {
+		&self.pixels
+	}
+
+	/// Returns whether the image is small enough to be used as a texture.
+	pub(crate) fn fits_max_size(&self) -> bool {
+		self.width <= MAX_IMAGE_SIZE && self.height <= MAX_IMAGE_SIZE
+	}
+
+	/// Copies part of the image into a new image.
+	fn crop(&self, x: u32, y: u32, width: u32, height: u32) -> Self {
+		let mut pixels = Vec::with_capacity(width as usize * height as usize * 4);
+		for row in y..(y + height) {
+			let start = (row as usize * self.width as usize + x as usize) * 4;
+			pixels.extend_from_slice(&self.pixels[start..(start + width as usize * 4)]);
+		}
+		Self {
+			width,
+			height,
+			pixels,
+		}
+	}
+}
+
+/// The direction the frames of a filmstrip are laid out in.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FilmstripOrientation {
+	/// The frames are laid out from left to right.
+	Horizontal,
+	/// The frames are laid out from top to bottom.
+	Vertical,
+}
+
+/// An image made of equally sized frames laid out in a row or column,
+/// like the frames of a knob turning from its minimum to maximum value.
+///
+/// Filmstrips that are longer than `MAX_IMAGE_SIZE` are split
+/// into several images, each holding as many whole frames as fit.
+#[derive(Clone, Debug)]
+pub struct Filmstrip {
+	/// The images that contain the frames, in order.
+	pub image_ids: Vec<ImageId>,
+	/// The number of frames in the filmstrip.
+	pub frame_count: usize,
+	/// The most frames each image contains.
+	pub frames_per_image: usize,
+	/// The direction the frames are laid out in.
+	pub orientation: FilmstripOrientation,
+	/// The width of each frame in pixels.
+	pub frame_width: u32,
+	/// The height of each frame in pixels.
+	pub frame_height: u32,
+}
+
+impl Filmstrip {
+	/// Splits an image into frames and returns the filmstrip (without
+	/// any images yet) along with the images to load for it.
+	///
+	/// Returns an error if there are no frames, the image can't be split
+	/// into whole frames, or a single frame is bigger than `MAX_IMAGE_SIZE`.
+	pub(crate) fn split(
+		image: Image,
+		frame_count: usize,
+		orientation: FilmstripOrientation,
+	) -> Result<(Self, Vec<Image>), InvalidImageError> {
+		let (length, thickness) = match orientation {
+			FilmstripOrientation::Horizontal => (image.width, image.height),
+			FilmstripOrientation::Vertical => (image.height, image.width),
+		};
+		let length = length as usize;
+		if frame_count == 0 || length % frame_count != 0 {
+			return Err(InvalidImageError);
+		}
+		let frame_length = (length / frame_count) as u32;
+		if frame_length == 0 || frame_length > MAX_IMAGE_SIZE || thickness > MAX_IMAGE_SIZE {
+			return Err(InvalidImageError);
+		}
+		let frames_per_image = ((MAX_IMAGE_SIZE / frame_length) as usize).min(frame_count);
+		let (frame_width, frame_height) = match orientation {
+			FilmstripOrientation::Horizontal => (frame_length, thickness),
+			FilmstripOrientation::Vertical => (thickness, frame_length),
+		};
+		let images = if frames_per_image == frame_count {
+			vec![image]
+		} else {
+			(0..frame_count)
+				.step_by(frames_per_image)
+				.map(|first_frame| {
+					let frames = frames_per_image.min(frame_count - first_frame) as u32;
+					let offset = first_frame as u32 * frame_length;
+					match orientation {
+						FilmstripOrientation::Horizontal => {
+							image.crop(offset, 0, frames * frame_length, thickness)
+						}
+						FilmstripOrientation::Vertical => {
+							image.crop(0, offset, thickness, frames * frame_length)
+						}
+					}
+				})
+				.collect()
+		};
+		let filmstrip = Self {
+			image_ids: vec![],
+			frame_count,
+			frames_per_image,
+			orientation,
+			frame_width,
+			frame_height,
+		};
+		Ok((filmstrip, images))
+	}
+
+	/// Gets the frame that represents a value from 0-1.
+	///
+	/// Values outside of that range use the first or last frame,
+	/// and NaN uses the first frame.
+	pub fn frame_for_value(&self, value: f32) -> usize {
+		if value.is_nan() {
+			return 0;
+		}
+		let last_frame = self.frame_count.saturating_sub(1);
+		let frame = (value.clamp(0.0, 1.0) * last_frame as f32).round() as usize;
+		frame.min(last_frame)
+	}
+
+	/// Gets the image that contains a frame and the part of that
+	/// image (in pixels) the frame is in.
+	pub fn frame_source(&self, frame: usize) -> (ImageId, Rectangle) {
+		let frame = frame.min(self.frame_count.saturating_sub(1));
+		let image_id = self.image_ids[frame / self.frames_per_image];
+		let frame = (frame % self.frames_per_image) as u32;
+		let (x, y) = match self.orientation {
+			FilmstripOrientation::Horizontal => (frame * self.frame_width, 0),
+			FilmstripOrientation::Vertical => (0, frame * self.frame_height),
+		};
+		let rectangle = Rectangle::new(
+			x as f32,
+			y as f32,
+			self.frame_width as f32,
+			self.frame_height as f32,
+		);
+		(image_id, rectangle)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	// 2x1 PNG images in each of the color types PNG supports
+	const GRAYSCALE: &[u8] = &[
+		0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44,
+		0x52, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x01, 0x08, 0x00, 0x00, 0x00, 0x00, 0xd1,
+		0x49, 0x20, 0x56, 0x00, 0x00, 0x00, 0x0b, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda, 0x63, 0x60,
+		0x38, 0x01, 0x00, 0x00, 0xcb, 0x00, 0xc9, 0xfa, 0x6c, 0xb4, 0x8b, 0x00, 0x00, 0x00, 0x00,
+		0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+	];
+	const GRAYSCALE_ALPHA: &[u8] = &[
+		0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44,
+		0x52, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x01, 0x08, 0x04, 0x00, 0x00, 0x00, 0x5e,
+		0x2b, 0xb7, 0x01, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda, 0x63, 0xe0,
+		0x12, 0x39, 0xf1, 0x1f, 0x00, 0x02, 0xf8, 0x01, 0xe6, 0x83, 0xce, 0xfe, 0xbb, 0x00, 0x00,
+		0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+	];
+	const RGB: &[u8] = &[
+		0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44,
+		0x52, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x01, 0x08, 0x02, 0x00, 0x00, 0x00, 0x7b,
+		0x40, 0xe8, 0xdd, 0x00, 0x00, 0x00, 0x0f, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda, 0x63, 0xf8,
+		0xcf, 0xc0, 0xc0, 0xd0, 0xf0, 0x1f, 0x00, 0x08, 0x00, 0x02, 0x7f, 0x25, 0x3e, 0xfc, 0x09,
+		0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+	];
+	const RGBA: &[u8] = &[
+		0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44,
+		0x52, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0xf4,
+		0x22, 0x7f, 0x8a, 0x00, 0x00, 0x00, 0x11, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda, 0x63, 0x60,
+		0x64, 0x62, 0x66, 0x61, 0x65, 0x63, 0xe7, 0x00, 0x00, 0x00, 0x81, 0x00, 0x25, 0xe5, 0x9a,
+		0x4f, 0x81, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+	];
+	const INDEXED: &[u8] = &[
+		0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44,
+		0x52, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x01, 0x08, 0x03, 0x00, 0x00, 0x00, 0xc3,
+		0xfc, 0x8f, 0xb8, 0x00, 0x00, 0x00, 0x06, 0x50, 0x4c, 0x54, 0x45, 0xff, 0x00, 0x00, 0x00,
+		0x00, 0xff, 0x6c, 0xa1, 0xfd, 0x8e, 0x00, 0x00, 0x00, 0x01, 0x74, 0x52, 0x4e, 0x53, 0x80,
+		0xad, 0x5e, 0x5b, 0x46, 0x00, 0x00, 0x00, 0x0b, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda, 0x63,
+		0x60, 0x60, 0x04, 0x00, 0x00, 0x04, 0x00, 0x02, 0x2c, 0xde, 0x48, 0xad, 0x00, 0x00, 0x00,
+		0x00, 0x49, 0x45, 0x4e, 0x44, 0xae, 0x42, 0x60, 0x82,
+	];
+	const RGB_16_BIT: &[u8] = &[
+		0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d, 0x49, 0x48, 0x44,
+		0x52, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x01, 0x10, 0x02, 0x00, 0x00, 0x00, 0x2b,
+		0xd0, 0x34, 0x9e, 0x00, 0x00, 0x00, 0x15, 0x49, 0x44, 0x41, 0x54, 0x78, 0xda, 0x63, 0xf8,
+		0xff, 0xbf, 0x81, 0x81, 0x81, 0x81, 0x91, 0x81, 0xe1, 0xff, 0xea, 0xb3, 0x00, 0x21, 0x1c,
+		0x04, 0xf7, 0x56, 0x8e, 0xd7, 0xaf, 0x00, 0x00, 0x00, 0x00, 0x49, 0x45, 0x4e, 0x44, 0xae,
+		0x42, 0x60, 0x82,
+	];
+
+	fn decode(data: &[u8]) -> Vec<u8> {
+		let image = Image::from_png(data).unwrap();
+		assert_eq!((image.width, image.height), (2, 1));
+		image.pixels().to_vec()
+	}
+
+	#[test]
+	fn decodes_grayscale_images() {
+		assert_eq!(decode(GRAYSCALE), vec![0, 0, 0, 255, 200, 200, 200, 255]);
+		assert_eq!(
+			decode(GRAYSCALE_ALPHA),
+			vec![10, 10, 10, 20, 200, 200, 200, 255]
+		);
+	}
+
+	#[test]
+	fn decodes_rgb_images() {
+		assert_eq!(decode(RGB), vec![255, 0, 0, 255, 0, 128, 255, 255]);
+		assert_eq!(decode(RGBA), vec![1, 2, 3, 4, 5, 6, 7, 8]);
+	}
+
+	#[test]
+	fn decodes_indexed_images_with_transparency() {
+		// only the first palette entry has an alpha value
+		assert_eq!(decode(INDEXED), vec![255, 0, 0, 128, 0, 0, 255, 255]);
+	}
+
+	#[test]
+	fn strips_16_bit_channels() {
+		assert_eq!(decode(RGB_16_BIT), vec![255, 128, 0, 255, 1, 0, 171, 255]);
+	}
+
+	#[test]
+	fn rejects_invalid_images() {
+		assert!(Image::from_png(&[]).is_err());
+		assert!(Image::from_png(&RGB[..RGB.len() - 20]).is_err());
+	}
+
+	#[test]
+	fn rejects_images_with_too_many_pixels() {
+		// only the header is needed, since the size is checked
+		// before the pixels are decoded
+		let mut data = vec![];
+		let mut writer = png::Encoder::new(&mut data, 65536, 65536)
+			.write_header()
+			.unwrap();
+		writer.write_chunk(*b"IDAT", &[]).unwrap();
+		drop(writer);
+		assert!(png::Decoder::new(&data[..]).read_info().is_ok());
+		assert!(Image::from_png(&data).is_err());
+	}
+
+	fn blank_image(width: u32, height: u32) -> Image {
+		Image {
+			width,
+			height,
+			pixels: vec![0; width as usize * height as usize * 4],
+		}
+	}
+
+	/// Splits a blank image into a filmstrip, numbering its images from 0.
+	fn filmstrip(
+		width: u32,
+		height: u32,
+		frame_count: usize,
+		orientation: FilmstripOrientation,
+	) -> Result<(Filmstrip, Vec<Image>), InvalidImageError> {
+		let (mut filmstrip, images) =
+			Filmstrip::split(blank_image(width, height), frame_count, orientation)?;
+		filmstrip.image_ids = (0..images.len()).collect();
+		Ok((filmstrip, images))
+	}
+
+	fn assert_source(
+		source: (ImageId, Rectangle),
+		image_id: ImageId,
+		x: f32,
+		y: f32,
+		width: f32,
+		height: f32,
+	) {
+		let (actual_id, rectangle) = source;
+		assert_eq!(
+			(
+				actual_id,
+				rectangle.x,
+				rectangle.y,
+				rectangle.width,
+				rectangle.height
+			),
+			(image_id, x, y, width, height)
+		);
+	}
+
+	#[test]
+	fn filmstrips_must_have_whole_frames() {
+		assert!(filmstrip(30, 10, 3, FilmstripOrientation::Horizontal).is_ok());
+		assert!(filmstrip(30, 10, 4, FilmstripOrientation::Horizontal).is_err());
+		assert!(filmstrip(10, 30, 4, FilmstripOrientation::Vertical).is_err());
+		assert!(filmstrip(30, 10, 0, FilmstripOrientation::Horizontal).is_err());
+		assert!(filmstrip(0, 10, 1, FilmstripOrientation::Horizontal).is_err());
+	}
+
+	#[test]
+	fn picks_frames_for_values() {
+		let (filmstrip, _) = filmstrip(50, 10, 5, FilmstripOrientation::Horizontal).unwrap();
+		assert_eq!(filmstrip.frame_for_value(0.0), 0);
+		assert_eq!(filmstrip.frame_for_value(0.3), 1);
+		assert_eq!(filmstrip.frame_for_value(0.4), 2);
+		assert_eq!(filmstrip.frame_for_value(1.0), 4);
+		assert_eq!(filmstrip.frame_for_value(-1.0), 0);
+		assert_eq!(filmstrip.frame_for_value(2.0), 4);
+		assert_eq!(filmstrip.frame_for_value(f32::NAN), 0);
+		assert_eq!(filmstrip.frame_for_value(f32::INFINITY), 4);
+	}
+
+	#[test]
+	fn single_frame_filmstrips_always_use_the_first_frame() {
+		let (filmstrip, _) = filmstrip(10, 10, 1, FilmstripOrientation::Vertical).unwrap();
+		for value in &[0.0, 0.5, 1.0, f32::NAN] {
+			assert_eq!(filmstrip.frame_for_value(*value), 0);
+		}
+		assert_source(filmstrip.frame_source(0), 0, 0.0, 0.0, 10.0, 10.0);
+	}
+
+	#[test]
+	fn finds_frame_sources() {
+		let (horizontal, images) = filmstrip(30, 10, 3, FilmstripOrientation::Horizontal).unwrap();
+		assert_eq!(images.len(), 1);
+		assert_source(horizontal.frame_source(0), 0, 0.0, 0.0, 10.0, 10.0);
+		assert_source(horizontal.frame_source(2), 0, 20.0, 0.0, 10.0, 10.0);
+		// frames past the end use the last frame
+		assert_source(horizontal.frame_source(5), 0, 20.0, 0.0, 10.0, 10.0);
+		let (vertical, _) = filmstrip(8, 24, 4, FilmstripOrientation::Vertical).unwrap();
+		assert_source(vertical.frame_source(1), 0, 0.0, 6.0, 8.0, 6.0);
+		assert_source(vertical.frame_source(3), 0, 0.0, 18.0, 8.0, 6.0);
+	}
+
+	#[test]
+	fn splits_long_filmstrips_into_several_images() {
+		// 81 frames fit in each image
+		let (filmstrip, images) =
+			filmstrip(2, 100 * 100, 100, FilmstripOrientation::Vertical).unwrap();
+		assert_eq!(filmstrip.frames_per_image, 81);
+		let sizes: Vec<(u32, u32)> = images
+			.iter()
+			.map(|image| (image.width, image.height))
+			.collect();
+		assert_eq!(sizes, vec![(2, 8100), (2, 1900)]);
+		assert_source(filmstrip.frame_source(80), 0, 0.0, 8000.0, 2.0, 100.0);
+		assert_source(filmstrip.frame_source(81), 1, 0.0, 0.0, 2.0, 100.0);
+		assert_source(filmstrip.frame_source(99), 1, 0.0, 1800.0, 2.0, 100.0);
+	}
+
+	#[test]
+	fn split_images_keep_their_pixels() {
+		// each frame is filled with its own index
+		let frame_size = MAX_IMAGE_SIZE as usize * 4;
+		let image = Image {
+			width: MAX_IMAGE_SIZE * 4,
+			height: 1,
+			pixels: (0..4).flat_map(|frame| vec![frame; frame_size]).collect(),
+		};
+		let (_, images) = Filmstrip::split(image, 4, FilmstripOrientation::Horizontal).unwrap();
+		assert_eq!(images.len(), 4);
+		for (frame, image) in images.iter().enumerate() {
+			assert_eq!(image.width, MAX_IMAGE_SIZE);
+			assert!(image.pixels.iter().all(|value| *value == frame as u8));
+		}
+	}
+
+	#[test]
+	fn rejects_frames_that_are_too_big() {
+		let size = MAX_IMAGE_SIZE + 1;
+		assert!(filmstrip(size, 1, 1, FilmstripOrientation::Horizontal).is_err());
+		assert!(filmstrip(1, size, 1, FilmstripOrientation::Horizontal).is_err());
+		assert!(filmstrip(size * 2, 1, 2, FilmstripOrientation::Horizontal).is_err());
+	}
+}
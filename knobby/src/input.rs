@@ -1,7 +1,7 @@
 use enum_map::Enum;
 
 /// A list of mouse buttons.
-#[derive(Copy, Clone, Enum, Debug)]
+#[derive(Copy, Clone, Enum, Debug, PartialEq, Eq)]
 pub enum MouseButton {
 	/// The left mouse button.
 	Left,
@@ -10,3 +10,107 @@ pub enum MouseButton {
 	/// The right mouse button.
 	Right,
 }
+
+/// The unit a scroll amount is measured in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ScrollUnit {
+	/// The amount is measured in lines (or "notches"), which is
+	/// typical of a mouse wheel.
+	Lines,
+	/// The amount is measured in pixels, which is typical of
+	/// trackpads and other precise scrolling devices.
+	Pixels,
+}
+
+/// A list of keyboard keys.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Key {
+	/// The tab key.
+	Tab,
+	/// The enter (or return) key.
+	Enter,
+	/// The escape key.
+	Escape,
+	/// The backspace key.
+	Backspace,
+	/// The delete key.
+	Delete,
+	/// The space bar.
+	Space,
+	/// The left arrow key.
+	Left,
+	/// The right arrow key.
+	Right,
+	/// The up arrow key.
+	Up,
+	/// The down arrow key.
+	Down,
+	/// The home key.
+	Home,
+	/// The end key.
+	End,
+	/// The page up key.
+	PageUp,
+	/// The page down key.
+	PageDown,
+	/// A key that corresponds to a letter, number, or symbol.
+	///
+	/// Letters are always lowercase. Use the text input events
+	/// to receive typed text.
+	Character(char),
+}
+
+/// A list of modifier keys.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Modifier {
+	/// Either shift key.
+	Shift,
+	/// Either control key.
+	Control,
+	/// Either alt (or option) key.
+	Alt,
+	/// The command key on Mac or the Windows key on Windows.
+	Command,
+}
+
+impl Modifier {
+	fn bit(self) -> u8 {
+		1 << self as u8
+	}
+}
+
+/// A set of modifier keys that are held down.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Modifiers(u8);
+
+impl Modifiers {
+	/// Creates an empty set of modifiers.
+	pub fn new() -> Self {
+		Self(0)
+	}
+
+	/// Returns a copy of the set with the given modifier added.
+	pub fn with(self, modifier: Modifier) -> Self {
+		Self(self.0 | modifier.bit())
+	}
+
+	/// Adds a modifier to the set.
+	pub fn insert(&mut self, modifier: Modifier) {
+		self.0 |= modifier.bit();
+	}
+
+	/// Removes a modifier from the set.
+	pub fn remove(&mut self, modifier: Modifier) {
+		self.0 &= !modifier.bit();
+	}
+
+	/// Returns `true` if the given modifier is held down.
+	pub fn contains(&self, modifier: Modifier) -> bool {
+		self.0 & modifier.bit() != 0
+	}
+
+	/// Returns `true` if no modifiers are held down.
+	pub fn is_empty(&self) -> bool {
+		self.0 == 0
+	}
+}
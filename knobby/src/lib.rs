@@ -30,16 +30,27 @@
 //! 		&mut self,
 //! 		event: Event<CustomEvent>,
 //! 		_controls: &mut Controls,
+//! 		_resources: &Resources,
+//! 		parameters: &Parameters,
 //! 		event_queue: &mut EventQueue<CustomEvent>,
 //! 	) {
 //! 		match event {
 //! 			// modify the parameter value when the knob is dragged up or down
-//! 			Event::Drag(id, button, x, y, dx, dy) => {
+//! 			Event::Drag(id, button, x, y, dx, dy, modifiers) => {
 //! 				event_queue.push(Event::SetParameter(
 //! 					self.parameter_index,
 //! 					(self.parameter_value - dy / 100.0).max(0.0).min(1.0),
 //! 				));
 //! 			}
+//! 			// reset the parameter when the knob is double clicked
+//! 			Event::DoubleClick(..) => {
+//! 				if let Some(info) = parameters.get(self.parameter_index) {
+//! 					event_queue.push(Event::SetParameter(
+//! 						self.parameter_index,
+//! 						info.default_normalized(),
+//! 					));
+//! 				}
+//! 			}
 //! 			// update the internal value when the parameter is changed
 //! 			Event::SetParameter(index, value) => {
 //! 				if index == self.parameter_index {
@@ -50,16 +61,23 @@
 //! 		}
 //! 	}
 //!
-//! 	fn draw(&self, control: &Control, canvas: &mut Canvas) {
+//! 	fn draw(
+//! 		&self,
+//! 		control: &Control,
+//! 		_resources: &Resources,
+//! 		parameters: &Parameters,
+//! 		_meters: &Meters,
+//! 		canvas: &mut Canvas,
+//! 	) {
 //! 		let center = control.rectangle.get_center();
 //! 		let radius = control.rectangle.height / 2.0;
 //! 		let nub_angle = 0.75 * PI + self.parameter_value * 1.5 * PI;
 //! 		let style = Style {
-//! 			mode: DrawMode::Stroke(4.0),
-//! 			color: Color::new(1.0, 1.0, 1.0, 1.0),
+//! 			mode: DrawMode::Stroke(Stroke::new(4.0)),
+//! 			paint: Paint::Solid(Color::new(1.0, 1.0, 1.0, 1.0)),
 //! 		};
 //! 		// draw the outside of the circle
-//! 		canvas.draw_circle(center, radius, style);
+//! 		canvas.draw_circle(center, radius, style.clone());
 //! 		// draw the "nub" representing where the knob is pointing
 //! 		canvas.draw_arc(
 //! 			ArcKind::Open,
@@ -69,9 +87,10 @@
 //! 			nub_angle + 0.25,
 //! 			style,
 //! 		);
-//! 		// draw a label below the knob
+//! 		// draw the parameter's name below the knob
+//! 		let info = parameters.get(self.parameter_index).unwrap();
 //! 		canvas.draw_text(
-//! 			"Label".to_string(),
+//! 			info.name.clone(),
 //! 			center.shifted(0.0, radius + 10.0),
 //! 			TextStyle {
 //! 				font_id: 0,
@@ -91,6 +110,12 @@
 //! let mut gui = Gui::new();
 //! ```
 //!
+//! ...describing the plugin's parameters...
+//!
+//! ```rust
+//! gui.parameters.add(0, ParameterInfo::new("Cutoff", 20.0, 20000.0, 1000.0));
+//! ```
+//!
 //! ...and adding controls to the GUI with the appropriate behaviors.
 //!
 //! ```rust
@@ -98,16 +123,28 @@
 //! 	ControlSettings {
 //! 		rectangle: Rectangle::new(50.0, 50.0, 100.0, 100.0),
 //! 		height: 0,
+//! 		parent: None,
+//! 		visible: true,
+//! 		focusable: false,
 //! 	},
 //! 	vec![Box::new(Knob::new(0))],
 //! );
 //! ```
 
+pub mod animation;
+pub mod audio;
 pub mod behavior;
 pub mod canvas;
 pub mod control;
 pub mod error;
 pub mod event;
+pub mod fill;
 pub mod geometry;
 pub mod gui;
+pub mod image;
 pub mod input;
+pub mod meter;
+pub mod parameter;
+pub mod path;
+pub mod stroker;
+pub mod svg;
@@ -0,0 +1,188 @@
+use std::sync::{
+	atomic::{AtomicU32, AtomicU8, Ordering},
+	Arc,
+};
+
+/// A unique identifier for a meter.
+pub type MeterId = usize;
+
+const FRESH: u8 = 0b100;
+
+/// Three copies of a meter's values, so the audio thread can write
+/// one copy while the GUI reads another without either side waiting.
+struct TripleBuffer {
+	buffers: [Box<[AtomicU32]>; 3],
+	// the index of the buffer that's neither being written nor read,
+	// plus the `FRESH` bit if it holds values the GUI hasn't seen yet
+	middle: AtomicU8,
+}
+
+impl TripleBuffer {
+	fn new(length: usize) -> Self {
+		let buffer = || (0..length).map(|_| AtomicU32::new(0)).collect();
+		Self {
+			buffers: [buffer(), buffer(), buffer()],
+			middle: AtomicU8::new(1),
+		}
+	}
+}
+
+/// Describes the meters the audio thread will publish.
+pub struct MeterLayout {
+	names: Vec<String>,
+	lengths: Vec<usize>,
+}
+
+impl MeterLayout {
+	/// Creates a new meter layout with no meters.
+	pub fn new() -> Self {
+		Self {
+			names: vec![],
+			lengths: vec![],
+		}
+	}
+
+	/// Adds a meter that holds a single value, like a signal level
+	/// or a playhead position.
+	pub fn add_value(&mut self, name: &str) -> MeterId {
+		self.add_array(name, 1)
+	}
+
+	/// Adds a meter that holds a fixed number of values,
+	/// like the bins of a spectrum analyzer.
+	pub fn add_array(&mut self, name: &str, length: usize) -> MeterId {
+		let id = self.names.len();
+		self.names.push(name.into());
+		self.lengths.push(length);
+		id
+	}
+}
+
+impl Default for MeterLayout {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+struct Shared {
+	names: Vec<String>,
+	buffers: Vec<TripleBuffer>,
+}
+
+impl Shared {
+	fn find(&self, name: &str) -> Option<MeterId> {
+		self.names.iter().position(|meter_name| meter_name == name)
+	}
+}
+
+/// The latest values published by the audio thread.
+///
+/// Unlike parameters, meter values aren't sent as events. Instead,
+/// behaviors can read the most recent values when they're drawn.
+pub struct Meters {
+	shared: Arc<Shared>,
+	front: Vec<u8>,
+	values: Vec<Vec<f32>>,
+}
+
+impl Meters {
+	pub(crate) fn new(layout: MeterLayout) -> (Self, MeterPublisher) {
+		let shared = Arc::new(Shared {
+			buffers: layout
+				.lengths
+				.iter()
+				.map(|length| TripleBuffer::new(*length))
+				.collect(),
+			names: layout.names,
+		});
+		let meters = Self {
+			shared: shared.clone(),
+			front: vec![0; layout.lengths.len()],
+			values: layout
+				.lengths
+				.iter()
+				.map(|length| vec![0.0; *length])
+				.collect(),
+		};
+		let publisher = MeterPublisher {
+			back: vec![2; layout.lengths.len()],
+			shared,
+		};
+		(meters, publisher)
+	}
+
+	/// Copies the most recently published values so they can be read.
+	///
+	/// Returns `true` if any meter had new values.
+	pub(crate) fn refresh(&mut self) -> bool {
+		let mut refreshed = false;
+		for (id, buffer) in self.shared.buffers.iter().enumerate() {
+			if buffer.middle.load(Ordering::Relaxed) & FRESH == 0 {
+				continue;
+			}
+			let previous_middle = buffer.middle.swap(self.front[id], Ordering::AcqRel);
+			self.front[id] = previous_middle & !FRESH;
+			let front = &buffer.buffers[self.front[id] as usize];
+			for (value, atomic) in self.values[id].iter_mut().zip(front.iter()) {
+				*value = f32::from_bits(atomic.load(Ordering::Relaxed));
+			}
+			refreshed = true;
+		}
+		refreshed
+	}
+
+	/// Returns the ID of the meter with the given name.
+	pub fn find(&self, name: &str) -> Option<MeterId> {
+		self.shared.find(name)
+	}
+
+	/// Returns the latest value of a single-value meter
+	/// (or the first value of an array meter).
+	pub fn get(&self, id: MeterId) -> Option<f32> {
+		self.values.get(id)?.first().copied()
+	}
+
+	/// Returns the latest values of a meter.
+	pub fn get_array(&self, id: MeterId) -> Option<&[f32]> {
+		self.values.get(id).map(|values| values.as_slice())
+	}
+}
+
+/// The audio thread's end of the meters.
+///
+/// A `MeterPublisher` can be moved to the audio thread. Publishing
+/// values never allocates memory or blocks.
+pub struct MeterPublisher {
+	shared: Arc<Shared>,
+	back: Vec<u8>,
+}
+
+impl MeterPublisher {
+	/// Returns the ID of the meter with the given name.
+	pub fn find(&self, name: &str) -> Option<MeterId> {
+		self.shared.find(name)
+	}
+
+	/// Publishes a new value for a single-value meter.
+	pub fn set(&mut self, id: MeterId, value: f32) {
+		self.set_array(id, &[value]);
+	}
+
+	/// Publishes new values for a meter.
+	///
+	/// If there are more values than the meter holds, the extra values
+	/// are ignored. If there are fewer, the remaining values are set to 0.
+	pub fn set_array(&mut self, id: MeterId, values: &[f32]) {
+		let buffer = match self.shared.buffers.get(id) {
+			Some(buffer) => buffer,
+			None => return,
+		};
+		let back = &buffer.buffers[self.back[id] as usize];
+		for (i, atomic) in back.iter().enumerate() {
+			let value = values.get(i).copied().unwrap_or(0.0);
+			atomic.store(value.to_bits(), Ordering::Relaxed);
+		}
+		let previous_middle = buffer.middle.swap(self.back[id] | FRESH, Ordering::AcqRel);
+		self.back[id] = previous_middle & !FRESH;
+	}
+}
@@ -0,0 +1,357 @@
+use std::collections::HashMap;
+
+/// A function that converts a plain parameter value to text.
+pub type ValueToString = Box<dyn Fn(f32) -> String>;
+
+/// A function that converts text to a plain parameter value.
+pub type StringToValue = Box<dyn Fn(&str) -> Option<f32>>;
+
+/// How a parameter's normalized value (from 0-1) maps to
+/// its plain value.
+#[derive(Copy, Clone, Debug)]
+pub enum Mapping {
+	/// The plain value changes linearly with the normalized value.
+	Linear,
+	/// The normalized value is raised to the given exponent before
+	/// being mapped to the parameter's range.
+	///
+	/// Exponents greater than 1 give more precision to the low end
+	/// of the range, and exponents less than 1 give more precision
+	/// to the high end.
+	Skewed(f32),
+	/// Equal changes in the normalized value multiply the plain value
+	/// by the same amount, which is a good fit for frequencies and
+	/// times. The minimum and maximum must both be greater than 0.
+	Logarithmic,
+}
+
+/// Describes a parameter of the plugin.
+///
+/// Behaviors can use the parameter info to convert between normalized
+/// values (from 0-1) and plain values, and to display values as text.
+pub struct ParameterInfo {
+	/// The name of the parameter.
+	pub name: String,
+	/// The lowest plain value of the parameter.
+	pub min: f32,
+	/// The highest plain value of the parameter.
+	pub max: f32,
+	/// The plain value the parameter should start at and reset to.
+	pub default: f32,
+	/// How the normalized value maps to the plain value.
+	pub mapping: Mapping,
+	/// The number of discrete values the parameter can have, or
+	/// `None` if the parameter is continuous.
+	pub steps: Option<u32>,
+	/// The unit to display after the value, like "dB" or "Hz".
+	pub unit: String,
+	/// A custom function for converting plain values to text.
+	pub to_string: Option<ValueToString>,
+	/// A custom function for converting text to plain values.
+	pub from_string: Option<StringToValue>,
+}
+
+impl ParameterInfo {
+	/// Creates a new continuous, linear parameter with no unit.
+	pub fn new(name: &str, min: f32, max: f32, default: f32) -> Self {
+		Self {
+			name: name.into(),
+			min,
+			max,
+			default,
+			mapping: Mapping::Linear,
+			steps: None,
+			unit: String::new(),
+			to_string: None,
+			from_string: None,
+		}
+	}
+
+	/// Clamps a plain value to the parameter's range and rounds it
+	/// to the nearest step (if the parameter has steps).
+	pub fn snap(&self, value: f32) -> f32 {
+		self.denormalize(self.normalize(value))
+	}
+
+	/// Converts a plain value to a normalized value (from 0-1).
+	///
+	/// NaN is treated as the lowest value.
+	pub fn normalize(&self, value: f32) -> f32 {
+		let value = value.clamp(self.min.min(self.max), self.max.max(self.min));
+		if self.max == self.min {
+			return 0.0;
+		}
+		let normalized = match self.mapping {
+			Mapping::Linear => (value - self.min) / (self.max - self.min),
+			Mapping::Skewed(exponent) => {
+				((value - self.min) / (self.max - self.min)).powf(1.0 / exponent)
+			}
+			Mapping::Logarithmic => (value / self.min).ln() / (self.max / self.min).ln(),
+		};
+		self.snap_normalized(normalized)
+	}
+
+	/// Converts a normalized value (from 0-1) to a plain value.
+	///
+	/// NaN is treated as 0.
+	pub fn denormalize(&self, normalized: f32) -> f32 {
+		let normalized = self.snap_normalized(normalized);
+		match self.mapping {
+			Mapping::Linear => self.min + (self.max - self.min) * normalized,
+			Mapping::Skewed(exponent) => {
+				self.min + (self.max - self.min) * normalized.powf(exponent)
+			}
+			Mapping::Logarithmic => self.min * (self.max / self.min).powf(normalized),
+		}
+	}
+
+	/// Gets the default value of the parameter as a normalized value.
+	pub fn default_normalized(&self) -> f32 {
+		self.normalize(self.default)
+	}
+
+	/// Converts a plain value to text that can be shown to the user.
+	pub fn format(&self, value: f32) -> String {
+		if let Some(to_string) = &self.to_string {
+			return to_string(value);
+		}
+		let precision = if self.steps.is_some() || value.abs() >= 100.0 {
+			0
+		} else if value.abs() >= 10.0 {
+			1
+		} else {
+			2
+		};
+		if self.unit.is_empty() {
+			format!("{:.*}", precision, value)
+		} else {
+			format!("{:.*} {}", precision, value, self.unit)
+		}
+	}
+
+	/// Converts text entered by the user to a plain value.
+	///
+	/// Returns `None` if the text isn't a valid value, including
+	/// values that aren't finite, like "nan" and "inf".
+	pub fn parse(&self, text: &str) -> Option<f32> {
+		let value = match &self.from_string {
+			Some(from_string) => from_string(text),
+			None => {
+				let text = text.trim();
+				let text = text.strip_suffix(self.unit.as_str()).unwrap_or(text);
+				text.trim().parse::<f32>().ok()
+			}
+		};
+		value
+			.filter(|value| value.is_finite())
+			.map(|value| self.snap(value))
+	}
+
+	fn snap_normalized(&self, normalized: f32) -> f32 {
+		// NaN would make it all the way to the audio thread otherwise
+		if normalized.is_nan() {
+			return 0.0;
+		}
+		let normalized = normalized.clamp(0.0, 1.0);
+		match self.steps {
+			Some(steps) if steps > 1 => {
+				let intervals = (steps - 1) as f32;
+				(normalized * intervals).round() / intervals
+			}
+			_ => normalized,
+		}
+	}
+}
+
+/// A list of the plugin's parameters.
+pub struct Parameters {
+	parameters: HashMap<i32, ParameterInfo>,
+}
+
+impl Parameters {
+	pub(crate) fn new() -> Self {
+		Self {
+			parameters: HashMap::new(),
+		}
+	}
+
+	/// Registers info for the parameter with the given index,
+	/// replacing any info that was previously registered.
+	pub fn add(&mut self, index: i32, info: ParameterInfo) {
+		self.parameters.insert(index, info);
+	}
+
+	/// Returns the info for the parameter with the given index.
+	pub fn get(&self, index: i32) -> Option<&ParameterInfo> {
+		self.parameters.get(&index)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn assert_close(actual: f32, expected: f32) {
+		assert!(
+			(actual - expected).abs() < 0.0001,
+			"expected {}, got {}",
+			expected,
+			actual
+		);
+	}
+
+	fn parameter(min: f32, max: f32, mapping: Mapping) -> ParameterInfo {
+		ParameterInfo {
+			mapping,
+			..ParameterInfo::new("Parameter", min, max, min)
+		}
+	}
+
+	#[test]
+	fn maps_linear_values() {
+		let info = parameter(-10.0, 30.0, Mapping::Linear);
+		assert_close(info.normalize(-10.0), 0.0);
+		assert_close(info.normalize(0.0), 0.25);
+		assert_close(info.normalize(30.0), 1.0);
+		assert_close(info.denormalize(0.25), 0.0);
+		assert_close(info.denormalize(1.0), 30.0);
+	}
+
+	#[test]
+	fn maps_skewed_values() {
+		let info = parameter(0.0, 100.0, Mapping::Skewed(2.0));
+		assert_close(info.normalize(25.0), 0.5);
+		assert_close(info.denormalize(0.5), 25.0);
+		assert_close(info.denormalize(0.0), 0.0);
+		assert_close(info.denormalize(1.0), 100.0);
+	}
+
+	#[test]
+	fn maps_logarithmic_values() {
+		let info = parameter(20.0, 20000.0, Mapping::Logarithmic);
+		assert_close(info.normalize(20.0), 0.0);
+		assert_close(info.normalize(20000.0), 1.0);
+		// every third of the range multiplies the value by 10
+		assert_close(info.normalize(200.0), 1.0 / 3.0);
+		assert!((info.denormalize(2.0 / 3.0) - 2000.0).abs() < 0.1);
+	}
+
+	#[test]
+	fn round_trips_values() {
+		for mapping in &[Mapping::Linear, Mapping::Skewed(0.5), Mapping::Logarithmic] {
+			let info = parameter(1.0, 50.0, *mapping);
+			for i in 0..=10 {
+				let normalized = i as f32 / 10.0;
+				assert_close(info.normalize(info.denormalize(normalized)), normalized);
+			}
+			for value in &[1.0, 7.5, 33.0, 50.0] {
+				let round_tripped = info.denormalize(info.normalize(*value));
+				assert!((round_tripped - value).abs() < 0.001);
+			}
+		}
+	}
+
+	#[test]
+	fn clamps_out_of_range_values() {
+		for mapping in &[Mapping::Linear, Mapping::Skewed(3.0), Mapping::Logarithmic] {
+			let info = parameter(1.0, 10.0, *mapping);
+			assert_close(info.normalize(-5.0), 0.0);
+			assert_close(info.normalize(100.0), 1.0);
+			assert_close(info.denormalize(-1.0), 1.0);
+			assert_close(info.denormalize(2.0), 10.0);
+			assert_close(info.snap(100.0), 10.0);
+		}
+	}
+
+	#[test]
+	fn treats_nan_as_the_lowest_value() {
+		for mapping in &[Mapping::Linear, Mapping::Skewed(2.0), Mapping::Logarithmic] {
+			let info = parameter(1.0, 10.0, *mapping);
+			assert_close(info.normalize(f32::NAN), 0.0);
+			assert_close(info.denormalize(f32::NAN), 1.0);
+			assert_close(info.snap(f32::NAN), 1.0);
+		}
+		let info = ParameterInfo {
+			steps: Some(3),
+			..ParameterInfo::new("Steps", 0.0, 2.0, 0.0)
+		};
+		assert_close(info.normalize(f32::NAN), 0.0);
+		assert_close(info.denormalize(f32::NAN), 0.0);
+	}
+
+	#[test]
+	fn handles_empty_ranges() {
+		for mapping in &[Mapping::Linear, Mapping::Skewed(2.0), Mapping::Logarithmic] {
+			let info = parameter(5.0, 5.0, *mapping);
+			assert_close(info.normalize(5.0), 0.0);
+			assert_close(info.normalize(100.0), 0.0);
+			assert_close(info.denormalize(0.5), 5.0);
+			assert_close(info.snap(0.0), 5.0);
+		}
+	}
+
+	#[test]
+	fn snaps_to_steps() {
+		let info = ParameterInfo {
+			steps: Some(5),
+			..ParameterInfo::new("Steps", 0.0, 8.0, 0.0)
+		};
+		assert_close(info.snap(2.9), 2.0);
+		assert_close(info.snap(3.1), 4.0);
+		assert_close(info.normalize(7.5), 1.0);
+		assert_close(info.denormalize(0.3), 2.0);
+		// without steps, values only get clamped
+		let info = parameter(0.0, 8.0, Mapping::Linear);
+		assert_close(info.snap(2.9), 2.9);
+	}
+
+	#[test]
+	fn formats_values() {
+		let info = ParameterInfo {
+			unit: "dB".into(),
+			..ParameterInfo::new("Gain", -60.0, 6.0, 0.0)
+		};
+		assert_eq!(info.format(1.234), "1.23 dB");
+		assert_eq!(info.format(-12.34), "-12.3 dB");
+		assert_eq!(info.format(440.0), "440 dB");
+		let info = ParameterInfo {
+			to_string: Some(Box::new(|value| format!("{} things", value))),
+			..ParameterInfo::new("Custom", 0.0, 10.0, 0.0)
+		};
+		assert_eq!(info.format(3.0), "3 things");
+	}
+
+	#[test]
+	fn parses_values() {
+		let info = ParameterInfo {
+			unit: "Hz".into(),
+			..ParameterInfo::new("Frequency", 20.0, 20000.0, 440.0)
+		};
+		assert_eq!(info.parse("440 Hz"), Some(440.0));
+		assert_eq!(info.parse(" 1000Hz "), Some(1000.0));
+		assert_eq!(info.parse("50"), Some(50.0));
+		// values are clamped to the range
+		assert_eq!(info.parse("5"), Some(20.0));
+		assert_eq!(info.parse("loud"), None);
+		assert_eq!(info.parse(""), None);
+		assert_eq!(info.parse("nan"), None);
+		assert_eq!(info.parse("NaN Hz"), None);
+		assert_eq!(info.parse("inf"), None);
+		assert_eq!(info.parse("-infinity Hz"), None);
+		let info = ParameterInfo {
+			from_string: Some(Box::new(|text| match text {
+				"on" => Some(1.0),
+				"off" => Some(0.0),
+				_ => None,
+			})),
+			..ParameterInfo::new("Switch", 0.0, 1.0, 0.0)
+		};
+		assert_eq!(info.parse("on"), Some(1.0));
+		assert_eq!(info.parse("maybe"), None);
+		let info = ParameterInfo {
+			from_string: Some(Box::new(|_| Some(f32::NAN))),
+			..ParameterInfo::new("Broken", 0.0, 1.0, 0.0)
+		};
+		assert_eq!(info.parse("anything"), None);
+	}
+}
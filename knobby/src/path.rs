@@ -0,0 +1,600 @@
+use crate::geometry::Point;
+use std::f32::consts::PI;
+
+/// The most line segments a single curve will be split into
+/// when a path is flattened.
+const MAX_SEGMENTS: usize = 1000;
+
+/// A single step in a path.
+#[derive(Copy, Clone, Debug)]
+pub enum PathCommand {
+	/// Starts a new subpath at the given point.
+	MoveTo(Point),
+	/// Draws a straight line to the given point.
+	LineTo(Point),
+	/// Draws a quadratic bezier curve with the given control point
+	/// and end point.
+	QuadTo(Point, Point),
+	/// Draws a cubic bezier curve with the given control points
+	/// and end point.
+	CubicTo(Point, Point, Point),
+	/// Draws part of an ellipse with the given x radius, y radius,
+	/// rotation (in radians), large arc flag, sweep flag, and end point.
+	///
+	/// The arguments work the same way as the arc command in SVG paths.
+	ArcTo(f32, f32, f32, bool, bool, Point),
+	/// Draws a straight line back to the start of the current subpath.
+	Close,
+}
+
+/// Decides which parts of a path are filled when its subpaths
+/// overlap or cross themselves.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum FillRule {
+	/// A point is inside the path if the subpaths around it don't
+	/// cancel each other out, so holes have to wind the opposite way
+	/// from the outline around them.
+	#[default]
+	NonZero,
+	/// A point is inside the path if it's surrounded by an odd
+	/// number of subpaths, so every overlapping subpath is a hole.
+	EvenOdd,
+}
+
+/// A shape made of lines and curves.
+///
+/// A path is made of one or more subpaths, each of which
+/// starts with `move_to`.
+#[derive(Clone, Debug, Default)]
+pub struct Path {
+	commands: Vec<PathCommand>,
+	fill_rule: FillRule,
+}
+
+impl Path {
+	/// Creates a new, empty path.
+	pub fn new() -> Self {
+		Self {
+			commands: vec![],
+			fill_rule: FillRule::NonZero,
+		}
+	}
+
+	/// Sets how overlapping subpaths are filled.
+	pub fn set_fill_rule(&mut self, fill_rule: FillRule) -> &mut Self {
+		self.fill_rule = fill_rule;
+		self
+	}
+
+	/// Gets how overlapping subpaths are filled.
+	pub fn fill_rule(&self) -> FillRule {
+		self.fill_rule
+	}
+
+	/// Starts a new subpath at the given point.
+	pub fn move_to(&mut self, point: Point) -> &mut Self {
+		self.commands.push(PathCommand::MoveTo(point));
+		self
+	}
+
+	/// Draws a straight line to the given point.
+	pub fn line_to(&mut self, point: Point) -> &mut Self {
+		self.commands.push(PathCommand::LineTo(point));
+		self
+	}
+
+	/// Draws a quadratic bezier curve to the given point.
+	pub fn quad_to(&mut self, control: Point, point: Point) -> &mut Self {
+		self.commands.push(PathCommand::QuadTo(control, point));
+		self
+	}
+
+	/// Draws a cubic bezier curve to the given point.
+	pub fn cubic_to(&mut self, control1: Point, control2: Point, point: Point) -> &mut Self {
+		self.commands
+			.push(PathCommand::CubicTo(control1, control2, point));
+		self
+	}
+
+	/// Draws part of an ellipse to the given point.
+	///
+	/// Of the four possible arcs between the current point and the end
+	/// point, `large_arc` picks one of the arcs that spans more than 180
+	/// degrees, and `sweep` picks one of the arcs that goes clockwise.
+	/// If the radii are too small to reach the end point, they're
+	/// scaled up until they fit.
+	pub fn arc_to(
+		&mut self,
+		radius_x: f32,
+		radius_y: f32,
+		rotation: f32,
+		large_arc: bool,
+		sweep: bool,
+		point: Point,
+	) -> &mut Self {
+		self.commands.push(PathCommand::ArcTo(
+			radius_x, radius_y, rotation, large_arc, sweep, point,
+		));
+		self
+	}
+
+	/// Draws a straight line back to the start of the current subpath
+	/// and closes it.
+	pub fn close(&mut self) -> &mut Self {
+		self.commands.push(PathCommand::Close);
+		self
+	}
+
+	/// Gets the commands that make up the path.
+	pub fn commands(&self) -> &[PathCommand] {
+		&self.commands
+	}
+
+	/// Converts the path to a list of subpaths made only
+	/// of straight lines.
+	///
+	/// `tolerance` is the maximum distance between a curve
+	/// and the lines that replace it.
+	pub fn flatten(&self, tolerance: f32) -> Vec<Subpath> {
+		let tolerance = tolerance.max(0.001);
+		let mut subpaths: Vec<Subpath> = vec![];
+		let mut current = Point::new(0.0, 0.0);
+		let mut subpath: Option<Subpath> = None;
+		for command in &self.commands {
+			match *command {
+				PathCommand::MoveTo(point) => {
+					subpaths.extend(subpath.take());
+					subpath = Some(Subpath::new(point));
+					current = point;
+				}
+				PathCommand::LineTo(point) => {
+					current_points(&mut subpath, current).push(point);
+					current = point;
+				}
+				PathCommand::QuadTo(control, point) => {
+					let points = current_points(&mut subpath, current);
+					flatten_quad(current, control, point, tolerance, points);
+					current = point;
+				}
+				PathCommand::CubicTo(control1, control2, point) => {
+					let points = current_points(&mut subpath, current);
+					flatten_cubic(current, control1, control2, point, tolerance, points);
+					current = point;
+				}
+				PathCommand::ArcTo(radius_x, radius_y, rotation, large_arc, sweep, point) => {
+					let points = current_points(&mut subpath, current);
+					flatten_arc(
+						current, radius_x, radius_y, rotation, large_arc, sweep, point, tolerance,
+						points,
+					);
+					current = point;
+				}
+				PathCommand::Close => {
+					if let Some(mut closed) = subpath.take() {
+						closed.closed = true;
+						current = closed.points[0];
+						subpaths.push(closed);
+					}
+				}
+			}
+		}
+		subpaths.extend(subpath);
+		subpaths
+	}
+}
+
+/// Part of a path that's been converted to straight lines.
+#[derive(Clone, Debug)]
+pub struct Subpath {
+	/// The points that the lines connect.
+	pub points: Vec<Point>,
+	/// Whether the last point connects back to the first point.
+	pub closed: bool,
+}
+
+impl Subpath {
+	fn new(start: Point) -> Self {
+		Self {
+			points: vec![start],
+			closed: false,
+		}
+	}
+}
+
+/// Returns the points of the subpath that's being flattened,
+/// starting a new subpath at the current point if there isn't one
+/// (like after a subpath is closed).
+fn current_points(subpath: &mut Option<Subpath>, current: Point) -> &mut Vec<Point> {
+	&mut subpath.get_or_insert_with(|| Subpath::new(current)).points
+}
+
+/// Returns the number of evenly spaced line segments needed to keep
+/// a curve within the tolerance, where `error` is the furthest a single
+/// line segment could be from the curve.
+fn segment_count(error: f32, tolerance: f32) -> usize {
+	// the error shrinks with the square of the number of segments
+	let segments = (error / tolerance).sqrt().ceil();
+	(segments as usize).clamp(1, MAX_SEGMENTS)
+}
+
+fn second_difference(a: Point, b: Point, c: Point) -> f32 {
+	(a.x - 2.0 * b.x + c.x).hypot(a.y - 2.0 * b.y + c.y)
+}
+
+fn flatten_quad(start: Point, control: Point, end: Point, tolerance: f32, points: &mut Vec<Point>) {
+	// the distance between a quadratic curve and n evenly spaced
+	// chords is at most |p0 - 2p1 + p2| / (4n^2)
+	let segments = segment_count(second_difference(start, control, end) / 4.0, tolerance);
+	for i in 1..=segments {
+		let t = i as f32 / segments as f32;
+		let mt = 1.0 - t;
+		points.push(Point::new(
+			mt * mt * start.x + 2.0 * mt * t * control.x + t * t * end.x,
+			mt * mt * start.y + 2.0 * mt * t * control.y + t * t * end.y,
+		));
+	}
+}
+
+fn flatten_cubic(
+	start: Point,
+	control1: Point,
+	control2: Point,
+	end: Point,
+	tolerance: f32,
+	points: &mut Vec<Point>,
+) {
+	// the distance between a cubic curve and n evenly spaced chords
+	// is at most 3 * max(|p0 - 2p1 + p2|, |p1 - 2p2 + p3|) / (4n^2)
+	let difference = second_difference(start, control1, control2)
+		.max(second_difference(control1, control2, end));
+	let segments = segment_count(difference * 0.75, tolerance);
+	for i in 1..=segments {
+		let t = i as f32 / segments as f32;
+		let mt = 1.0 - t;
+		let a = mt * mt * mt;
+		let b = 3.0 * mt * mt * t;
+		let c = 3.0 * mt * t * t;
+		let d = t * t * t;
+		points.push(Point::new(
+			a * start.x + b * control1.x + c * control2.x + d * end.x,
+			a * start.y + b * control1.y + c * control2.y + d * end.y,
+		));
+	}
+}
+
+/// Flattens an elliptical arc, following the conversion from
+/// endpoint to center parameterization in the SVG spec.
+#[allow(clippy::too_many_arguments)]
+fn flatten_arc(
+	start: Point,
+	radius_x: f32,
+	radius_y: f32,
+	rotation: f32,
+	large_arc: bool,
+	sweep: bool,
+	end: Point,
+	tolerance: f32,
+	points: &mut Vec<Point>,
+) {
+	if (start.x - end.x).abs() < f32::EPSILON && (start.y - end.y).abs() < f32::EPSILON {
+		return;
+	}
+	let mut radius_x = radius_x.abs();
+	let mut radius_y = radius_y.abs();
+	if radius_x == 0.0 || radius_y == 0.0 {
+		points.push(end);
+		return;
+	}
+	let (sin, cos) = rotation.sin_cos();
+	// the midpoint between the start and end in the ellipse's
+	// coordinate space
+	let half_dx = (start.x - end.x) / 2.0;
+	let half_dy = (start.y - end.y) / 2.0;
+	let x1 = cos * half_dx + sin * half_dy;
+	let y1 = -sin * half_dx + cos * half_dy;
+	// scale up the radii if they can't reach the end point
+	let lambda = (x1 * x1) / (radius_x * radius_x) + (y1 * y1) / (radius_y * radius_y);
+	if lambda > 1.0 {
+		radius_x *= lambda.sqrt();
+		radius_y *= lambda.sqrt();
+	}
+	let rx2 = radius_x * radius_x;
+	let ry2 = radius_y * radius_y;
+	let numerator = (rx2 * ry2 - rx2 * y1 * y1 - ry2 * x1 * x1).max(0.0);
+	let denominator = rx2 * y1 * y1 + ry2 * x1 * x1;
+	let mut coefficient = (numerator / denominator).sqrt();
+	if large_arc == sweep {
+		coefficient = -coefficient;
+	}
+	let center_x1 = coefficient * radius_x * y1 / radius_y;
+	let center_y1 = -coefficient * radius_y * x1 / radius_x;
+	let center = Point::new(
+		cos * center_x1 - sin * center_y1 + (start.x + end.x) / 2.0,
+		sin * center_x1 + cos * center_y1 + (start.y + end.y) / 2.0,
+	);
+	let start_angle = ((y1 - center_y1) / radius_y).atan2((x1 - center_x1) / radius_x);
+	let end_angle = ((-y1 - center_y1) / radius_y).atan2((-x1 - center_x1) / radius_x);
+	let mut sweep_angle = end_angle - start_angle;
+	if sweep && sweep_angle < 0.0 {
+		sweep_angle += 2.0 * PI;
+	} else if !sweep && sweep_angle > 0.0 {
+		sweep_angle -= 2.0 * PI;
+	}
+	// pick an angle step so the distance between each chord
+	// and the arc stays within the tolerance
+	let radius = radius_x.max(radius_y);
+	let step = 2.0 * (1.0 - tolerance / radius).max(-1.0).acos();
+	let segments = ((sweep_angle.abs() / step).ceil() as usize).clamp(1, MAX_SEGMENTS);
+	for i in 1..segments {
+		let angle = start_angle + sweep_angle * (i as f32 / segments as f32);
+		let x = radius_x * angle.cos();
+		let y = radius_y * angle.sin();
+		points.push(Point::new(
+			cos * x - sin * y + center.x,
+			sin * x + cos * y + center.y,
+		));
+	}
+	// use the exact end point to avoid gaps from rounding errors
+	points.push(end);
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const TOLERANCE: f32 = 0.1;
+
+	fn point_distance(a: Point, b: Point) -> f32 {
+		(b.x - a.x).hypot(b.y - a.y)
+	}
+
+	fn distance_to_line(point: Point, start: Point, end: Point) -> f32 {
+		let dx = end.x - start.x;
+		let dy = end.y - start.y;
+		let length_squared = dx * dx + dy * dy;
+		if length_squared == 0.0 {
+			return point_distance(point, start);
+		}
+		let amount = (((point.x - start.x) * dx + (point.y - start.y) * dy) / length_squared)
+			.clamp(0.0, 1.0);
+		point_distance(
+			point,
+			Point::new(start.x + dx * amount, start.y + dy * amount),
+		)
+	}
+
+	fn distance_to_lines(point: Point, points: &[Point]) -> f32 {
+		points
+			.windows(2)
+			.map(|line| distance_to_line(point, line[0], line[1]))
+			.fold(f32::INFINITY, f32::min)
+	}
+
+	fn assert_points_close(a: Point, b: Point) {
+		assert!(
+			point_distance(a, b) < 0.001,
+			"expected {:?}, got {:?}",
+			b,
+			a
+		);
+	}
+
+	fn flatten_single(path: &Path) -> Vec<Point> {
+		let subpaths = path.flatten(TOLERANCE);
+		assert_eq!(subpaths.len(), 1);
+		subpaths[0].points.clone()
+	}
+
+	/// Checks that every point along a curve is within the tolerance
+	/// of the lines it was flattened to.
+	fn assert_within_tolerance(points: &[Point], curve: impl Fn(f32) -> Point) {
+		for i in 0..=1000 {
+			let point = curve(i as f32 / 1000.0);
+			let distance = distance_to_lines(point, points);
+			// leave a little room for rounding errors
+			assert!(
+				distance <= TOLERANCE * 1.01,
+				"{:?} is {} away from the lines",
+				point,
+				distance
+			);
+		}
+	}
+
+	#[test]
+	fn flattens_quadratic_curves() {
+		let (start, control, end) = (
+			Point::new(0.0, 0.0),
+			Point::new(50.0, 100.0),
+			Point::new(100.0, 0.0),
+		);
+		let points = flatten_single(Path::new().move_to(start).quad_to(control, end));
+		assert!(points.len() > 2);
+		assert_points_close(points[0], start);
+		assert_points_close(*points.last().unwrap(), end);
+		assert_within_tolerance(&points, |t| {
+			let mt = 1.0 - t;
+			Point::new(
+				mt * mt * start.x + 2.0 * mt * t * control.x + t * t * end.x,
+				mt * mt * start.y + 2.0 * mt * t * control.y + t * t * end.y,
+			)
+		});
+	}
+
+	#[test]
+	fn flattens_cubic_curves() {
+		let (start, control1, control2, end) = (
+			Point::new(0.0, 0.0),
+			Point::new(0.0, 100.0),
+			Point::new(100.0, -100.0),
+			Point::new(100.0, 0.0),
+		);
+		let points = flatten_single(Path::new().move_to(start).cubic_to(control1, control2, end));
+		assert_points_close(points[0], start);
+		assert_points_close(*points.last().unwrap(), end);
+		assert_within_tolerance(&points, |t| {
+			let mt = 1.0 - t;
+			let (a, b, c, d) = (mt * mt * mt, 3.0 * mt * mt * t, 3.0 * mt * t * t, t * t * t);
+			Point::new(
+				a * start.x + b * control1.x + c * control2.x + d * end.x,
+				a * start.y + b * control1.y + c * control2.y + d * end.y,
+			)
+		});
+	}
+
+	#[test]
+	fn straight_curves_use_one_line() {
+		let points = flatten_single(
+			Path::new()
+				.move_to(Point::new(0.0, 0.0))
+				.quad_to(Point::new(5.0, 5.0), Point::new(10.0, 10.0)),
+		);
+		assert_eq!(points.len(), 2);
+	}
+
+	#[test]
+	fn flattens_arcs() {
+		let start = Point::new(0.0, 0.0);
+		let end = Point::new(20.0, 0.0);
+		let center = Point::new(10.0, 0.0);
+		for &sweep in &[true, false] {
+			let points = flatten_single(
+				Path::new()
+					.move_to(start)
+					.arc_to(10.0, 10.0, 0.0, false, sweep, end),
+			);
+			assert_points_close(points[0], start);
+			assert_points_close(*points.last().unwrap(), end);
+			for point in &points {
+				assert!((point_distance(*point, center) - 10.0).abs() < 0.001);
+				// sweeping goes clockwise, which is through negative y
+				// values from this start point
+				if sweep {
+					assert!(point.y <= 0.001);
+				} else {
+					assert!(point.y >= -0.001);
+				}
+			}
+			assert_within_tolerance(&points, |t| {
+				let angle = PI + PI * t * if sweep { 1.0 } else { -1.0 };
+				Point::new(center.x + 10.0 * angle.cos(), center.y + 10.0 * angle.sin())
+			});
+		}
+	}
+
+	#[test]
+	fn picks_large_and_small_arcs() {
+		// two circles of radius 10 go through both points, with their
+		// centers at (5, -8.66) and (5, 8.66)
+		let arc_length = |large_arc: bool| {
+			let points = flatten_single(Path::new().move_to(Point::new(0.0, 0.0)).arc_to(
+				10.0,
+				10.0,
+				0.0,
+				large_arc,
+				true,
+				Point::new(10.0, 0.0),
+			));
+			points
+				.windows(2)
+				.map(|line| point_distance(line[0], line[1]))
+				.sum::<f32>()
+		};
+		// the small arc spans 60 degrees and the large arc spans 300
+		// (the lines are slightly shorter than the arcs they replace)
+		assert!((arc_length(false) / (10.0 * PI / 3.0) - 1.0).abs() < 0.01);
+		assert!((arc_length(true) / (10.0 * PI * 5.0 / 3.0) - 1.0).abs() < 0.01);
+	}
+
+	#[test]
+	fn arcs_with_zero_radii_are_lines() {
+		for &(radius_x, radius_y) in &[(0.0, 10.0), (10.0, 0.0), (0.0, 0.0)] {
+			let points = flatten_single(Path::new().move_to(Point::new(0.0, 0.0)).arc_to(
+				radius_x,
+				radius_y,
+				0.0,
+				false,
+				true,
+				Point::new(20.0, 5.0),
+			));
+			assert_eq!(points.len(), 2);
+			assert_points_close(points[1], Point::new(20.0, 5.0));
+		}
+	}
+
+	#[test]
+	fn scales_up_radii_that_are_too_small() {
+		let center = Point::new(10.0, 0.0);
+		let points = flatten_single(Path::new().move_to(Point::new(0.0, 0.0)).arc_to(
+			1.0,
+			1.0,
+			0.0,
+			false,
+			true,
+			Point::new(20.0, 0.0),
+		));
+		assert!(points.len() > 2);
+		// the radii are scaled up to 10, making a half circle
+		for point in &points {
+			assert!((point_distance(*point, center) - 10.0).abs() < 0.001);
+		}
+	}
+
+	#[test]
+	fn skips_arcs_that_end_where_they_start() {
+		let points = flatten_single(Path::new().move_to(Point::new(5.0, 5.0)).arc_to(
+			10.0,
+			10.0,
+			0.0,
+			true,
+			true,
+			Point::new(5.0, 5.0),
+		));
+		assert_eq!(points.len(), 1);
+	}
+
+	#[test]
+	fn limits_the_number_of_segments() {
+		// a tiny tolerance and a huge curve would need far more
+		// segments than the limit
+		let subpaths = Path::new()
+			.move_to(Point::new(0.0, 0.0))
+			.quad_to(Point::new(0.0, 1_000_000.0), Point::new(1.0, 0.0))
+			.arc_to(
+				1_000_000.0,
+				1_000_000.0,
+				0.0,
+				true,
+				true,
+				Point::new(2.0, 0.0),
+			)
+			.flatten(0.0);
+		let points = &subpaths[0].points;
+		// the start point, plus the quad and arc segments
+		assert_eq!(points.len(), 1 + MAX_SEGMENTS * 2);
+		assert_points_close(points[MAX_SEGMENTS], Point::new(1.0, 0.0));
+		assert_points_close(*points.last().unwrap(), Point::new(2.0, 0.0));
+	}
+
+	#[test]
+	fn splits_paths_into_subpaths() {
+		let subpaths = Path::new()
+			.move_to(Point::new(0.0, 0.0))
+			.line_to(Point::new(10.0, 0.0))
+			.line_to(Point::new(10.0, 10.0))
+			.close()
+			// lines after a subpath is closed start from its first point
+			.line_to(Point::new(0.0, 10.0))
+			.move_to(Point::new(20.0, 20.0))
+			.line_to(Point::new(30.0, 20.0))
+			.flatten(TOLERANCE);
+		assert_eq!(subpaths.len(), 3);
+		assert!(subpaths[0].closed);
+		assert_eq!(subpaths[0].points.len(), 3);
+		assert!(!subpaths[1].closed);
+		assert_points_close(subpaths[1].points[0], Point::new(0.0, 0.0));
+		assert_points_close(subpaths[1].points[1], Point::new(0.0, 10.0));
+		assert!(!subpaths[2].closed);
+		assert_points_close(subpaths[2].points[0], Point::new(20.0, 20.0));
+	}
+}
@@ -0,0 +1,560 @@
+use crate::{
+	canvas::{LineCap, LineJoin, Stroke},
+	geometry::Point,
+};
+use std::f32::consts::PI;
+
+/// The maximum distance (in pixels) between the edge of a round
+/// cap or join and the triangles used to draw it.
+const ROUND_TOLERANCE: f32 = 0.1;
+
+/// Points closer together than this are treated as the same point.
+const EPSILON: f32 = 0.0001;
+
+/// The most dashes a single line will be split into. Lines that
+/// would need more are drawn solid instead.
+const MAX_DASHES: usize = 10000;
+
+/// Converts a line (or the outline of a shape, if `closed` is `true`)
+/// to triangles that cover the area drawn by the given stroke.
+///
+/// This lets every backend draw caps, joins, and dashes the same way.
+/// Triangles may overlap where lines meet, so backends drawing
+/// translucent strokes will see slightly darker corners.
+pub fn stroke_triangles(points: &[Point], closed: bool, stroke: &Stroke) -> Vec<[Point; 3]> {
+	let mut points: Vec<Point> = points.to_vec();
+	points.dedup_by(|a, b| distance(*a, *b) < EPSILON);
+	let mut closed = closed;
+	if closed && points.len() > 1 && distance(points[0], points[points.len() - 1]) < EPSILON {
+		points.pop();
+	}
+	if points.len() < 3 {
+		// a closed shape with fewer than three corners is just a line
+		closed = false;
+	}
+	let mut stroker = Stroker {
+		stroke,
+		half_width: stroke.width / 2.0,
+		triangles: vec![],
+	};
+	if stroker.half_width <= 0.0 || points.is_empty() {
+		return vec![];
+	}
+	match dash_pattern(stroke) {
+		Some(pattern) if dash_count(&points, closed, &pattern) <= MAX_DASHES => {
+			for dash in split_dashes(&points, closed, &pattern, stroke.dash_offset) {
+				stroker.add_line(&dash, false);
+			}
+		}
+		_ => stroker.add_line(&points, closed),
+	}
+	stroker.triangles
+}
+
+struct Stroker<'a> {
+	stroke: &'a Stroke,
+	half_width: f32,
+	triangles: Vec<[Point; 3]>,
+}
+
+impl<'a> Stroker<'a> {
+	fn add_line(&mut self, points: &[Point], closed: bool) {
+		if points.len() == 1 {
+			// a single point only shows up if it has caps
+			self.add_dot(points[0]);
+			return;
+		}
+		let segment_count = if closed {
+			points.len()
+		} else {
+			points.len() - 1
+		};
+		for i in 0..segment_count {
+			let start = points[i];
+			let end = points[(i + 1) % points.len()];
+			let normal = scale(normal(start, end), self.half_width);
+			self.add_quad(
+				add(start, normal),
+				add(end, normal),
+				subtract(end, normal),
+				subtract(start, normal),
+			);
+		}
+		// add joins at every corner (including the first and last point
+		// if the line is closed)
+		let corners = if closed {
+			0..points.len()
+		} else {
+			1..points.len() - 1
+		};
+		for i in corners {
+			let previous = points[(i + points.len() - 1) % points.len()];
+			let next = points[(i + 1) % points.len()];
+			self.add_join(previous, points[i], next);
+		}
+		if !closed {
+			self.add_cap(points[1], points[0]);
+			self.add_cap(points[points.len() - 2], points[points.len() - 1]);
+		}
+	}
+
+	fn add_quad(&mut self, a: Point, b: Point, c: Point, d: Point) {
+		self.triangles.push([a, b, c]);
+		self.triangles.push([a, c, d]);
+	}
+
+	/// Adds the join between the line from `previous` to `corner`
+	/// and the line from `corner` to `next`.
+	fn add_join(&mut self, previous: Point, corner: Point, next: Point) {
+		let incoming = direction(previous, corner);
+		let outgoing = direction(corner, next);
+		let turn = cross(incoming, outgoing);
+		if turn.abs() < EPSILON && dot(incoming, outgoing) > 0.0 {
+			// the lines are straight, so there's no gap to fill
+			return;
+		}
+		// the gap is on the outside of the turn
+		let side = if turn > 0.0 { -1.0 } else { 1.0 };
+		let outer_incoming = scale(normal(previous, corner), self.half_width * side);
+		let outer_outgoing = scale(normal(corner, next), self.half_width * side);
+		let start = add(corner, outer_incoming);
+		let end = add(corner, outer_outgoing);
+		match self.stroke.join {
+			LineJoin::Bevel => self.triangles.push([corner, start, end]),
+			LineJoin::Round => self.add_fan(corner, outer_incoming, outer_outgoing, -side),
+			LineJoin::Miter => {
+				let sum = add(outer_incoming, outer_outgoing);
+				let sum_length_squared = dot(sum, sum);
+				// the miter's length (relative to the stroke width) is
+				// 1 / sin(angle / 2), which works out to half_width * 2 / |sum|
+				let too_long = sum_length_squared < EPSILON
+					|| self.half_width * 2.0 / sum_length_squared.sqrt() > self.stroke.miter_limit;
+				if too_long {
+					self.triangles.push([corner, start, end]);
+				} else {
+					let tip = add(
+						corner,
+						scale(
+							sum,
+							2.0 * self.half_width * self.half_width / sum_length_squared,
+						),
+					);
+					self.triangles.push([corner, start, tip]);
+					self.triangles.push([corner, tip, end]);
+				}
+			}
+		}
+	}
+
+	/// Adds the cap at the `end` of the line from `previous` to `end`.
+	fn add_cap(&mut self, previous: Point, end: Point) {
+		let forward = scale(direction(previous, end), self.half_width);
+		let normal = scale(normal(previous, end), self.half_width);
+		match self.stroke.cap {
+			LineCap::Butt => {}
+			LineCap::Round => self.add_fan(end, normal, scale(normal, -1.0), -1.0),
+			LineCap::Square => self.add_quad(
+				add(end, normal),
+				add(add(end, normal), forward),
+				add(subtract(end, normal), forward),
+				subtract(end, normal),
+			),
+		}
+	}
+
+	/// Adds the caps for a line with no length.
+	fn add_dot(&mut self, point: Point) {
+		match self.stroke.cap {
+			LineCap::Butt => {}
+			LineCap::Round => {
+				let start = Point::new(self.half_width, 0.0);
+				self.add_fan(point, start, scale(start, -1.0), 1.0);
+				self.add_fan(point, scale(start, -1.0), start, 1.0);
+			}
+			LineCap::Square => {
+				let half_width = self.half_width;
+				self.add_quad(
+					Point::new(point.x - half_width, point.y - half_width),
+					Point::new(point.x + half_width, point.y - half_width),
+					Point::new(point.x + half_width, point.y + half_width),
+					Point::new(point.x - half_width, point.y + half_width),
+				);
+			}
+		}
+	}
+
+	/// Adds a fan of triangles around `center` sweeping from the
+	/// `from` offset to the `to` offset. The sweep goes towards
+	/// increasing angles if `direction` is positive and decreasing
+	/// angles otherwise.
+	fn add_fan(&mut self, center: Point, from: Point, to: Point, direction: f32) {
+		let start_angle = from.y.atan2(from.x);
+		let mut sweep = to.y.atan2(to.x) - start_angle;
+		if direction > 0.0 {
+			while sweep < 0.0 {
+				sweep += 2.0 * PI;
+			}
+		} else {
+			while sweep > 0.0 {
+				sweep -= 2.0 * PI;
+			}
+		}
+		let step = 2.0 * (1.0 - ROUND_TOLERANCE / self.half_width).max(-1.0).acos();
+		let segments = ((sweep.abs() / step).ceil() as usize).clamp(1, 256);
+		let mut previous = add(center, from);
+		for i in 1..=segments {
+			let angle = start_angle + sweep * (i as f32 / segments as f32);
+			let point = Point::new(
+				center.x + self.half_width * angle.cos(),
+				center.y + self.half_width * angle.sin(),
+			);
+			self.triangles.push([center, previous, point]);
+			previous = point;
+		}
+	}
+}
+
+/// Returns the stroke's dash pattern (repeated to an even length),
+/// or `None` if the stroke is solid.
+fn dash_pattern(stroke: &Stroke) -> Option<Vec<f32>> {
+	let total: f32 = stroke.dashes.iter().sum();
+	if stroke.dashes.is_empty()
+		|| total <= EPSILON
+		|| stroke.dashes.iter().any(|length| *length < 0.0)
+	{
+		return None;
+	}
+	let mut pattern = stroke.dashes.clone();
+	if pattern.len() % 2 == 1 {
+		pattern.extend_from_slice(&stroke.dashes);
+	}
+	Some(pattern)
+}
+
+/// Returns roughly how many dashes a line will be split into.
+fn dash_count(points: &[Point], closed: bool, pattern: &[f32]) -> usize {
+	let mut length: f32 = points
+		.windows(2)
+		.map(|line| distance(line[0], line[1]))
+		.sum();
+	if closed {
+		length += distance(points[points.len() - 1], points[0]);
+	}
+	let total: f32 = pattern.iter().sum();
+	// every pass through the pattern has a dash for every pair of lengths,
+	// and the offset can add one more pass
+	let passes = (length / total).ceil() + 1.0;
+	(passes * (pattern.len() / 2) as f32).min(usize::MAX as f32) as usize
+}
+
+/// Splits a line into the pieces that are inside dashes.
+fn split_dashes(points: &[Point], closed: bool, pattern: &[f32], offset: f32) -> Vec<Vec<Point>> {
+	let total: f32 = pattern.iter().sum();
+	// find where in the pattern the line starts
+	let mut offset = offset.rem_euclid(total);
+	let mut index = 0;
+	while offset > 0.0 && offset >= pattern[index] {
+		offset -= pattern[index];
+		index = (index + 1) % pattern.len();
+	}
+	// even entries in the pattern are dashes, odd entries are gaps
+	let mut remaining = pattern[index] - offset;
+	let mut dashes = vec![];
+	let mut dash = if index % 2 == 0 {
+		vec![points[0]]
+	} else {
+		vec![]
+	};
+	let segment_count = if closed {
+		points.len()
+	} else {
+		points.len() - 1
+	};
+	for i in 0..segment_count {
+		let start = points[i];
+		let end = points[(i + 1) % points.len()];
+		let length = distance(start, end);
+		let mut position = 0.0;
+		loop {
+			if remaining > length - position + EPSILON {
+				remaining -= length - position;
+				if index % 2 == 0 {
+					dash.push(end);
+				}
+				break;
+			}
+			position += remaining;
+			let point = lerp(start, end, position / length);
+			if index % 2 == 0 {
+				dash.push(point);
+				dash.dedup_by(|a, b| distance(*a, *b) < EPSILON);
+				if dash.len() == 1 {
+					// give zero-length dashes a direction so
+					// their caps line up with the line
+					dash.push(add(point, scale(direction(start, end), EPSILON * 2.0)));
+				}
+				dashes.push(std::mem::take(&mut dash));
+			} else {
+				dash.push(point);
+			}
+			index = (index + 1) % pattern.len();
+			remaining = pattern[index];
+		}
+	}
+	dash.dedup_by(|a, b| distance(*a, *b) < EPSILON);
+	if dash.len() > 1 {
+		dashes.push(dash);
+	}
+	dashes
+}
+
+fn add(a: Point, b: Point) -> Point {
+	Point::new(a.x + b.x, a.y + b.y)
+}
+
+fn subtract(a: Point, b: Point) -> Point {
+	Point::new(a.x - b.x, a.y - b.y)
+}
+
+fn scale(point: Point, amount: f32) -> Point {
+	Point::new(point.x * amount, point.y * amount)
+}
+
+fn dot(a: Point, b: Point) -> f32 {
+	a.x * b.x + a.y * b.y
+}
+
+fn cross(a: Point, b: Point) -> f32 {
+	a.x * b.y - a.y * b.x
+}
+
+fn distance(a: Point, b: Point) -> f32 {
+	(b.x - a.x).hypot(b.y - a.y)
+}
+
+fn lerp(a: Point, b: Point, amount: f32) -> Point {
+	Point::new(a.x + (b.x - a.x) * amount, a.y + (b.y - a.y) * amount)
+}
+
+/// Returns the unit vector pointing from `a` to `b`.
+fn direction(a: Point, b: Point) -> Point {
+	let length = distance(a, b);
+	if length < EPSILON {
+		return Point::new(1.0, 0.0);
+	}
+	scale(subtract(b, a), 1.0 / length)
+}
+
+/// Returns the unit vector perpendicular to the line from `a` to `b`.
+fn normal(a: Point, b: Point) -> Point {
+	let direction = direction(a, b);
+	Point::new(-direction.y, direction.x)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn area(triangles: &[[Point; 3]]) -> f32 {
+		triangles
+			.iter()
+			.map(|[a, b, c]| cross(subtract(*b, *a), subtract(*c, *a)).abs() / 2.0)
+			.sum()
+	}
+
+	fn assert_close(actual: f32, expected: f32) {
+		assert!(
+			(actual - expected).abs() < 0.01,
+			"expected {}, got {}",
+			expected,
+			actual
+		);
+	}
+
+	fn assert_between(actual: f32, min: f32, max: f32) {
+		assert!(
+			actual >= min - 0.0001 && actual <= max + 0.0001,
+			"expected {} to be between {} and {}",
+			actual,
+			min,
+			max
+		);
+	}
+
+	fn circle_area(radius: f32) -> f32 {
+		PI * radius * radius
+	}
+
+	fn line(length: f32) -> Vec<Point> {
+		vec![Point::new(0.0, 0.0), Point::new(length, 0.0)]
+	}
+
+	/// Returns the start and end x position of each dash along a
+	/// horizontal line.
+	fn dash_ranges(length: f32, dashes: &[f32], offset: f32) -> Vec<(f32, f32)> {
+		let stroke = Stroke {
+			dashes: dashes.to_vec(),
+			..Stroke::new(1.0)
+		};
+		let pattern = dash_pattern(&stroke).unwrap();
+		split_dashes(&line(length), false, &pattern, offset)
+			.iter()
+			.map(|dash| (dash[0].x, dash[dash.len() - 1].x))
+			.collect()
+	}
+
+	fn assert_ranges(actual: Vec<(f32, f32)>, expected: &[(f32, f32)]) {
+		assert_eq!(actual.len(), expected.len(), "got {:?}", actual);
+		for ((start, end), (expected_start, expected_end)) in actual.iter().zip(expected) {
+			assert_close(*start, *expected_start);
+			assert_close(*end, *expected_end);
+		}
+	}
+
+	#[test]
+	fn splits_lines_into_dashes() {
+		assert_ranges(
+			dash_ranges(10.0, &[2.0, 2.0], 0.0),
+			&[(0.0, 2.0), (4.0, 6.0), (8.0, 10.0)],
+		);
+	}
+
+	#[test]
+	fn starts_dashes_at_the_offset() {
+		assert_ranges(
+			dash_ranges(10.0, &[2.0, 2.0], 1.0),
+			&[(0.0, 1.0), (3.0, 5.0), (7.0, 9.0)],
+		);
+		// negative offsets and offsets longer than the pattern wrap around
+		assert_ranges(
+			dash_ranges(10.0, &[2.0, 2.0], -1.0),
+			&[(1.0, 3.0), (5.0, 7.0), (9.0, 10.0)],
+		);
+		assert_ranges(
+			dash_ranges(10.0, &[2.0, 2.0], 9.0),
+			&[(0.0, 1.0), (3.0, 5.0), (7.0, 9.0)],
+		);
+	}
+
+	#[test]
+	fn repeats_odd_length_patterns() {
+		let stroke = Stroke {
+			dashes: vec![2.0, 1.0, 3.0],
+			..Stroke::new(1.0)
+		};
+		assert_eq!(
+			dash_pattern(&stroke).unwrap(),
+			vec![2.0, 1.0, 3.0, 2.0, 1.0, 3.0]
+		);
+		// the second pass swaps which lengths are dashes and gaps
+		assert_ranges(
+			dash_ranges(12.0, &[2.0, 1.0, 3.0], 0.0),
+			&[(0.0, 2.0), (3.0, 6.0), (8.0, 9.0)],
+		);
+	}
+
+	#[test]
+	fn ignores_invalid_dash_patterns() {
+		for dashes in &[vec![], vec![0.0, 0.0], vec![2.0, -1.0]] {
+			let stroke = Stroke {
+				dashes: dashes.clone(),
+				..Stroke::new(1.0)
+			};
+			assert!(dash_pattern(&stroke).is_none());
+		}
+	}
+
+	#[test]
+	fn draws_lines_with_too_many_dashes_solid() {
+		let stroke = Stroke {
+			dashes: vec![0.01, 0.01],
+			..Stroke::new(2.0)
+		};
+		let triangles = stroke_triangles(&line(1000.0), false, &stroke);
+		assert_eq!(triangles.len(), 2);
+		assert_close(area(&triangles), 2000.0);
+	}
+
+	#[test]
+	fn adds_caps() {
+		let stroke = |cap| Stroke {
+			cap,
+			..Stroke::new(2.0)
+		};
+		let butt = stroke_triangles(&line(10.0), false, &stroke(LineCap::Butt));
+		assert_close(area(&butt), 20.0);
+		let square = stroke_triangles(&line(10.0), false, &stroke(LineCap::Square));
+		assert_close(area(&square), 24.0);
+		// round caps add a circle, minus at most the tolerance
+		// where the triangles cut across the curve
+		let round = stroke_triangles(&line(10.0), false, &stroke(LineCap::Round));
+		assert_between(area(&round) - 20.0, circle_area(1.0 - ROUND_TOLERANCE), PI);
+		// a single point is only drawn if it has caps
+		let point = [Point::new(5.0, 5.0)];
+		assert!(stroke_triangles(&point, false, &stroke(LineCap::Butt)).is_empty());
+		assert_close(
+			area(&stroke_triangles(&point, false, &stroke(LineCap::Square))),
+			4.0,
+		);
+	}
+
+	#[test]
+	fn adds_joins() {
+		// a right angle, where each join fills part of the 1x1 square
+		// outside the corner
+		let corner = [
+			Point::new(0.0, 0.0),
+			Point::new(10.0, 0.0),
+			Point::new(10.0, 10.0),
+		];
+		let area_with_join = |join| {
+			let stroke = Stroke {
+				join,
+				..Stroke::new(2.0)
+			};
+			area(&stroke_triangles(&corner, false, &stroke))
+		};
+		assert_close(area_with_join(LineJoin::Miter), 41.0);
+		assert_close(area_with_join(LineJoin::Bevel), 40.5);
+		assert_between(
+			area_with_join(LineJoin::Round) - 40.0,
+			circle_area(1.0 - ROUND_TOLERANCE) / 4.0,
+			PI / 4.0,
+		);
+	}
+
+	#[test]
+	fn switches_from_miter_to_bevel_at_the_miter_limit() {
+		// the miter of a right angle is sqrt(2) times the stroke width
+		let corner = [
+			Point::new(0.0, 0.0),
+			Point::new(10.0, 0.0),
+			Point::new(10.0, 10.0),
+		];
+		let area_with_limit = |miter_limit| {
+			let stroke = Stroke {
+				miter_limit,
+				..Stroke::new(2.0)
+			};
+			area(&stroke_triangles(&corner, false, &stroke))
+		};
+		assert_close(area_with_limit(1.5), 41.0);
+		assert_close(area_with_limit(1.4), 40.5);
+	}
+
+	#[test]
+	fn joins_every_corner_of_closed_shapes() {
+		let square = [
+			Point::new(0.0, 0.0),
+			Point::new(10.0, 0.0),
+			Point::new(10.0, 10.0),
+			Point::new(0.0, 10.0),
+		];
+		let stroke = Stroke {
+			cap: LineCap::Square,
+			..Stroke::new(2.0)
+		};
+		// four sides plus four mitered corners, with no caps
+		assert_close(area(&stroke_triangles(&square, true, &stroke)), 84.0);
+	}
+}
@@ -0,0 +1,1001 @@
+use crate::{
+	canvas::{Color, LineCap, LineJoin, Stroke},
+	error::InvalidSvgError,
+	geometry::{Point, Rectangle, Transform},
+	path::{FillRule, Path},
+};
+
+/// A vector image loaded from an SVG file.
+///
+/// Only a subset of SVG is supported:
+/// - `path`, `rect`, `circle`, `ellipse`, `line`, `polyline`,
+///   and `polygon` elements, which can be grouped with `g` elements
+/// - solid fill and stroke colors (including `currentColor`),
+///   opacity, fill rules, stroke widths, caps, joins, and dash patterns
+/// - transforms
+///
+/// Text, images, gradients, patterns, masks, clip paths, and
+/// CSS stylesheets are ignored.
+#[derive(Clone)]
+pub struct Svg {
+	/// The area of the SVG's coordinate space that's visible.
+	pub view_box: Rectangle,
+	pub(crate) shapes: Vec<SvgShape>,
+}
+
+impl Svg {
+	/// Parses the contents of an SVG file.
+	pub fn parse(svg_data: &str) -> Result<Self, InvalidSvgError> {
+		let document = roxmltree::Document::parse(svg_data).map_err(|_| InvalidSvgError)?;
+		let root = document.root_element();
+		if root.tag_name().name() != "svg" {
+			return Err(InvalidSvgError);
+		}
+		let view_box = match root.attribute("viewBox") {
+			Some(view_box) => {
+				let numbers = parse_number_list(view_box);
+				if numbers.len() != 4 {
+					return Err(InvalidSvgError);
+				}
+				Rectangle::new(numbers[0], numbers[1], numbers[2], numbers[3])
+			}
+			None => {
+				let width = root.attribute("width").and_then(parse_length);
+				let height = root.attribute("height").and_then(parse_length);
+				match (width, height) {
+					(Some(width), Some(height)) => Rectangle::new(0.0, 0.0, width, height),
+					_ => return Err(InvalidSvgError),
+				}
+			}
+		};
+		if view_box.width <= 0.0 || view_box.height <= 0.0 {
+			return Err(InvalidSvgError);
+		}
+		let mut shapes = vec![];
+		add_element(root, &ElementStyle::new(), &mut shapes);
+		Ok(Self { view_box, shapes })
+	}
+}
+
+/// A color used to fill or stroke a shape in an SVG.
+#[derive(Copy, Clone)]
+pub(crate) enum SvgColor {
+	Color(Color),
+	/// The color passed to `Canvas::draw_svg`.
+	CurrentColor,
+}
+
+/// How a shape in an SVG is filled or stroked.
+#[derive(Copy, Clone)]
+pub(crate) struct SvgPaint {
+	pub(crate) color: SvgColor,
+	pub(crate) opacity: f32,
+}
+
+impl SvgPaint {
+	pub(crate) fn resolve(&self, current_color: Color) -> Color {
+		let color = match self.color {
+			SvgColor::Color(color) => color,
+			SvgColor::CurrentColor => current_color,
+		};
+		Color::new(
+			color.red,
+			color.green,
+			color.blue,
+			color.alpha * self.opacity,
+		)
+	}
+}
+
+/// A single shape in an SVG.
+#[derive(Clone)]
+pub(crate) struct SvgShape {
+	pub(crate) path: Path,
+	pub(crate) transform: Option<Transform>,
+	pub(crate) fill: Option<SvgPaint>,
+	pub(crate) stroke: Option<(SvgPaint, Stroke)>,
+}
+
+/// The properties that elements inherit from their parents.
+#[derive(Clone)]
+struct ElementStyle {
+	fill: Option<SvgColor>,
+	fill_opacity: f32,
+	fill_rule: FillRule,
+	stroke: Option<SvgColor>,
+	stroke_opacity: f32,
+	stroke_style: Stroke,
+	// opacity isn't inherited, but an element's opacity affects
+	// all of its children, so we multiply it into the children's opacity
+	opacity: f32,
+	transform: Option<Transform>,
+}
+
+impl ElementStyle {
+	fn new() -> Self {
+		Self {
+			fill: Some(SvgColor::Color(Color::new(0.0, 0.0, 0.0, 1.0))),
+			fill_opacity: 1.0,
+			fill_rule: FillRule::NonZero,
+			stroke: None,
+			stroke_opacity: 1.0,
+			stroke_style: Stroke::new(1.0),
+			opacity: 1.0,
+			transform: None,
+		}
+	}
+
+	/// Updates the style with a presentation attribute or
+	/// CSS property. Invalid values are ignored.
+	fn apply(&mut self, name: &str, value: &str) {
+		let value = value.trim();
+		match name {
+			"fill" => {
+				if let Some(paint) = parse_paint(value) {
+					self.fill = paint;
+				}
+			}
+			"stroke" => {
+				if let Some(paint) = parse_paint(value) {
+					self.stroke = paint;
+				}
+			}
+			"fill-opacity" => {
+				if let Some(opacity) = parse_opacity(value) {
+					self.fill_opacity = opacity;
+				}
+			}
+			"fill-rule" => match value {
+				"nonzero" => self.fill_rule = FillRule::NonZero,
+				"evenodd" => self.fill_rule = FillRule::EvenOdd,
+				_ => {}
+			},
+			"stroke-opacity" => {
+				if let Some(opacity) = parse_opacity(value) {
+					self.stroke_opacity = opacity;
+				}
+			}
+			"opacity" => {
+				if let Some(opacity) = parse_opacity(value) {
+					self.opacity *= opacity;
+				}
+			}
+			"stroke-width" => {
+				if let Some(width) = parse_length(value) {
+					self.stroke_style.width = width;
+				}
+			}
+			"stroke-linecap" => match value {
+				"butt" => self.stroke_style.cap = LineCap::Butt,
+				"round" => self.stroke_style.cap = LineCap::Round,
+				"square" => self.stroke_style.cap = LineCap::Square,
+				_ => {}
+			},
+			"stroke-linejoin" => match value {
+				"miter" => self.stroke_style.join = LineJoin::Miter,
+				"round" => self.stroke_style.join = LineJoin::Round,
+				"bevel" => self.stroke_style.join = LineJoin::Bevel,
+				_ => {}
+			},
+			"stroke-miterlimit" => {
+				if let Some(limit) = parse_number(value) {
+					self.stroke_style.miter_limit = limit;
+				}
+			}
+			"stroke-dasharray" => {
+				self.stroke_style.dashes = if value == "none" {
+					vec![]
+				} else {
+					parse_number_list(value)
+				};
+			}
+			"stroke-dashoffset" => {
+				if let Some(offset) = parse_length(value) {
+					self.stroke_style.dash_offset = offset;
+				}
+			}
+			_ => {}
+		}
+	}
+}
+
+/// Adds the shapes for an element and its children.
+fn add_element(node: roxmltree::Node, parent_style: &ElementStyle, shapes: &mut Vec<SvgShape>) {
+	let mut style = parent_style.clone();
+	for attribute in node.attributes() {
+		style.apply(attribute.name(), attribute.value());
+	}
+	// the style attribute takes priority over presentation attributes
+	if let Some(declarations) = node.attribute("style") {
+		for declaration in declarations.split(';') {
+			if let Some(colon) = declaration.find(':') {
+				let name = declaration[..colon].trim();
+				style.apply(name, &declaration[colon + 1..]);
+				if name == "display" && declaration[colon + 1..].trim() == "none" {
+					return;
+				}
+			}
+		}
+	}
+	if node.attribute("display") == Some("none") {
+		return;
+	}
+	if let Some(transform) = node.attribute("transform").map(parse_transform) {
+		style.transform = Some(match style.transform {
+			Some(parent_transform) => transform.then(parent_transform),
+			None => transform,
+		});
+	}
+	let number = |name: &str| node.attribute(name).and_then(parse_length).unwrap_or(0.0);
+	let mut path = match node.tag_name().name() {
+		"svg" | "g" | "a" => {
+			for child in node.children().filter(|child| child.is_element()) {
+				add_element(child, &style, shapes);
+			}
+			return;
+		}
+		"path" => parse_path_data(node.attribute("d").unwrap_or("")),
+		"rect" => rectangle_path(
+			Rectangle::new(number("x"), number("y"), number("width"), number("height")),
+			node.attribute("rx").and_then(parse_length),
+			node.attribute("ry").and_then(parse_length),
+		),
+		"circle" => ellipse_path(
+			Point::new(number("cx"), number("cy")),
+			number("r"),
+			number("r"),
+		),
+		"ellipse" => ellipse_path(
+			Point::new(number("cx"), number("cy")),
+			number("rx"),
+			number("ry"),
+		),
+		"line" => {
+			let mut path = Path::new();
+			path.move_to(Point::new(number("x1"), number("y1")))
+				.line_to(Point::new(number("x2"), number("y2")));
+			path
+		}
+		name @ "polyline" | name @ "polygon" => {
+			let numbers = parse_number_list(node.attribute("points").unwrap_or(""));
+			let mut path = Path::new();
+			for (i, pair) in numbers.chunks_exact(2).enumerate() {
+				let point = Point::new(pair[0], pair[1]);
+				if i == 0 {
+					path.move_to(point);
+				} else {
+					path.line_to(point);
+				}
+			}
+			if name == "polygon" && !numbers.is_empty() {
+				path.close();
+			}
+			path
+		}
+		// everything else (like text and definitions) isn't supported
+		_ => return,
+	};
+	if path.commands().is_empty() {
+		return;
+	}
+	path.set_fill_rule(style.fill_rule);
+	let fill = style.fill.map(|color| SvgPaint {
+		color,
+		opacity: style.fill_opacity * style.opacity,
+	});
+	let stroke = match style.stroke {
+		Some(color) if style.stroke_style.width > 0.0 => Some((
+			SvgPaint {
+				color,
+				opacity: style.stroke_opacity * style.opacity,
+			},
+			style.stroke_style.clone(),
+		)),
+		_ => None,
+	};
+	if fill.is_some() || stroke.is_some() {
+		shapes.push(SvgShape {
+			path,
+			transform: style.transform,
+			fill,
+			stroke,
+		});
+	}
+}
+
+fn rectangle_path(rectangle: Rectangle, radius_x: Option<f32>, radius_y: Option<f32>) -> Path {
+	let mut path = Path::new();
+	if rectangle.width <= 0.0 || rectangle.height <= 0.0 {
+		return path;
+	}
+	// if only one radius is given, it's used for both
+	let radius_x = radius_x.or(radius_y).unwrap_or(0.0);
+	let radius_y = radius_y.unwrap_or(radius_x);
+	let radius_x = radius_x.clamp(0.0, rectangle.width / 2.0);
+	let radius_y = radius_y.clamp(0.0, rectangle.height / 2.0);
+	let left = rectangle.x;
+	let top = rectangle.y;
+	let right = rectangle.x + rectangle.width;
+	let bottom = rectangle.y + rectangle.height;
+	if radius_x == 0.0 || radius_y == 0.0 {
+		path.move_to(Point::new(left, top))
+			.line_to(Point::new(right, top))
+			.line_to(Point::new(right, bottom))
+			.line_to(Point::new(left, bottom))
+			.close();
+		return path;
+	}
+	path.move_to(Point::new(left + radius_x, top))
+		.line_to(Point::new(right - radius_x, top))
+		.arc_to(
+			radius_x,
+			radius_y,
+			0.0,
+			false,
+			true,
+			Point::new(right, top + radius_y),
+		)
+		.line_to(Point::new(right, bottom - radius_y))
+		.arc_to(
+			radius_x,
+			radius_y,
+			0.0,
+			false,
+			true,
+			Point::new(right - radius_x, bottom),
+		)
+		.line_to(Point::new(left + radius_x, bottom))
+		.arc_to(
+			radius_x,
+			radius_y,
+			0.0,
+			false,
+			true,
+			Point::new(left, bottom - radius_y),
+		)
+		.line_to(Point::new(left, top + radius_y))
+		.arc_to(
+			radius_x,
+			radius_y,
+			0.0,
+			false,
+			true,
+			Point::new(left + radius_x, top),
+		)
+		.close();
+	path
+}
+
+fn ellipse_path(center: Point, radius_x: f32, radius_y: f32) -> Path {
+	let mut path = Path::new();
+	if radius_x <= 0.0 || radius_y <= 0.0 {
+		return path;
+	}
+	// an arc can't start and end at the same point,
+	// so the ellipse is drawn in two halves
+	path.move_to(center.shifted(radius_x, 0.0))
+		.arc_to(
+			radius_x,
+			radius_y,
+			0.0,
+			false,
+			true,
+			center.shifted(-radius_x, 0.0),
+		)
+		.arc_to(
+			radius_x,
+			radius_y,
+			0.0,
+			false,
+			true,
+			center.shifted(radius_x, 0.0),
+		)
+		.close();
+	path
+}
+
+/// Parses a fill or stroke value. Returns `None` if the value is
+/// invalid and `Some(None)` if the shape shouldn't be painted.
+fn parse_paint(value: &str) -> Option<Option<SvgColor>> {
+	match value {
+		"none" => Some(None),
+		"currentColor" => Some(Some(SvgColor::CurrentColor)),
+		// gradients and patterns aren't supported
+		_ if value.starts_with("url(") => Some(None),
+		_ => parse_color(value).map(|color| Some(SvgColor::Color(color))),
+	}
+}
+
+fn parse_color(value: &str) -> Option<Color> {
+	if let Some(hex) = value.strip_prefix('#') {
+		let digit = |i: usize| {
+			hex.get(i..i + 1)
+				.and_then(|digit| u8::from_str_radix(digit, 16).ok())
+		};
+		let (red, green, blue) = match hex.len() {
+			3 => (digit(0)? * 17, digit(1)? * 17, digit(2)? * 17),
+			6 => (
+				digit(0)? * 16 + digit(1)?,
+				digit(2)? * 16 + digit(3)?,
+				digit(4)? * 16 + digit(5)?,
+			),
+			_ => return None,
+		};
+		return Some(Color::new(
+			red as f32 / 255.0,
+			green as f32 / 255.0,
+			blue as f32 / 255.0,
+			1.0,
+		));
+	}
+	if let Some(arguments) = value
+		.strip_prefix("rgb(")
+		.and_then(|value| value.strip_suffix(')'))
+	{
+		let components: Vec<f32> = arguments
+			.split(',')
+			.map(|component| {
+				let component = component.trim();
+				match component.strip_suffix('%') {
+					Some(percentage) => parse_number(percentage).map(|value| value / 100.0),
+					None => parse_number(component).map(|value| value / 255.0),
+				}
+			})
+			.collect::<Option<_>>()?;
+		if components.len() != 3 {
+			return None;
+		}
+		return Some(Color::new(
+			components[0].clamp(0.0, 1.0),
+			components[1].clamp(0.0, 1.0),
+			components[2].clamp(0.0, 1.0),
+			1.0,
+		));
+	}
+	let hex = match value {
+		"black" => "#000000",
+		"white" => "#ffffff",
+		"red" => "#ff0000",
+		"green" => "#008000",
+		"blue" => "#0000ff",
+		"yellow" => "#ffff00",
+		"cyan" | "aqua" => "#00ffff",
+		"magenta" | "fuchsia" => "#ff00ff",
+		"gray" | "grey" => "#808080",
+		"silver" => "#c0c0c0",
+		"maroon" => "#800000",
+		"purple" => "#800080",
+		"lime" => "#00ff00",
+		"navy" => "#000080",
+		"teal" => "#008080",
+		"olive" => "#808000",
+		"orange" => "#ffa500",
+		"transparent" => return Some(Color::new(0.0, 0.0, 0.0, 0.0)),
+		_ => return None,
+	};
+	parse_color(hex)
+}
+
+fn parse_opacity(value: &str) -> Option<f32> {
+	let opacity = match value.strip_suffix('%') {
+		Some(percentage) => parse_number(percentage)? / 100.0,
+		None => parse_number(value)?,
+	};
+	Some(opacity.clamp(0.0, 1.0))
+}
+
+/// Parses a number, rejecting numbers that aren't finite (including
+/// ones too big to fit in an `f32`), which can't be drawn.
+fn parse_number(value: &str) -> Option<f32> {
+	value
+		.trim()
+		.parse()
+		.ok()
+		.filter(|number: &f32| number.is_finite())
+}
+
+/// Parses a length in user units (or pixels, which are the same thing).
+/// Other units, like percentages, aren't supported.
+fn parse_length(value: &str) -> Option<f32> {
+	let value = value.trim();
+	parse_number(value.strip_suffix("px").unwrap_or(value))
+}
+
+fn parse_number_list(value: &str) -> Vec<f32> {
+	let mut parser = NumberParser::new(value);
+	let mut numbers = vec![];
+	while let Some(number) = parser.number() {
+		numbers.push(number);
+	}
+	numbers
+}
+
+fn parse_transform(value: &str) -> Transform {
+	let mut transform = Transform::identity();
+	for function in value.split(')') {
+		let (name, arguments) = match function.find('(') {
+			Some(parenthesis) => (
+				function[..parenthesis].trim_matches(|c: char| c.is_whitespace() || c == ','),
+				parse_number_list(&function[parenthesis + 1..]),
+			),
+			None => continue,
+		};
+		let argument = |i: usize| arguments.get(i).copied();
+		let next = match (name, arguments.len()) {
+			("matrix", 6) => Transform {
+				a: arguments[0],
+				b: arguments[1],
+				c: arguments[2],
+				d: arguments[3],
+				e: arguments[4],
+				f: arguments[5],
+			},
+			("translate", 1) | ("translate", 2) => {
+				Transform::translation(arguments[0], argument(1).unwrap_or(0.0))
+			}
+			("scale", 1) | ("scale", 2) => {
+				Transform::scaling(arguments[0], argument(1).unwrap_or(arguments[0]))
+			}
+			("rotate", 1) => Transform::rotation(arguments[0].to_radians()),
+			("rotate", 3) => Transform::rotation_around(
+				arguments[0].to_radians(),
+				Point::new(arguments[1], arguments[2]),
+			),
+			("skewX", 1) => Transform {
+				c: arguments[0].to_radians().tan(),
+				..Transform::identity()
+			},
+			("skewY", 1) => Transform {
+				b: arguments[0].to_radians().tan(),
+				..Transform::identity()
+			},
+			// an invalid transform list means no transform is applied
+			_ => return Transform::identity(),
+		};
+		// the functions are applied from right to left
+		transform = next.then(transform);
+	}
+	transform
+}
+
+/// Parses the `d` attribute of a path element. If there's an error
+/// in the path data, the path is drawn up to the error.
+fn parse_path_data(data: &str) -> Path {
+	let mut path = Path::new();
+	let mut parser = NumberParser::new(data);
+	let mut command: Option<char> = None;
+	let mut current = Point::new(0.0, 0.0);
+	let mut subpath_start = current;
+	// the control points of the previous curve, used by the
+	// smooth curve commands
+	let mut previous_cubic_control: Option<Point> = None;
+	let mut previous_quad_control: Option<Point> = None;
+	loop {
+		parser.skip_separators();
+		command = match parser.peek() {
+			None => break,
+			Some(c) if c.is_ascii_alphabetic() => {
+				parser.advance();
+				Some(c)
+			}
+			// numbers after a move command are treated as line commands
+			Some(_) => match command {
+				Some('M') => Some('L'),
+				Some('m') => Some('l'),
+				Some('Z') | Some('z') | None => break,
+				repeated => repeated,
+			},
+		};
+		let c = match command {
+			Some(c) => c,
+			None => break,
+		};
+		let origin = if c.is_ascii_lowercase() {
+			current
+		} else {
+			Point::new(0.0, 0.0)
+		};
+		let mut cubic_control = None;
+		let mut quad_control = None;
+		match c.to_ascii_uppercase() {
+			'M' => match parser.point() {
+				Some(point) => {
+					current = point.shifted(origin.x, origin.y);
+					subpath_start = current;
+					path.move_to(current);
+				}
+				None => break,
+			},
+			'L' => match parser.point() {
+				Some(point) => {
+					current = point.shifted(origin.x, origin.y);
+					path.line_to(current);
+				}
+				None => break,
+			},
+			'H' => match parser.number() {
+				Some(x) => {
+					current = Point::new(x + origin.x, current.y);
+					path.line_to(current);
+				}
+				None => break,
+			},
+			'V' => match parser.number() {
+				Some(y) => {
+					current = Point::new(current.x, y + origin.y);
+					path.line_to(current);
+				}
+				None => break,
+			},
+			'C' => match (parser.point(), parser.point(), parser.point()) {
+				(Some(control1), Some(control2), Some(point)) => {
+					let control2 = control2.shifted(origin.x, origin.y);
+					current = point.shifted(origin.x, origin.y);
+					path.cubic_to(control1.shifted(origin.x, origin.y), control2, current);
+					cubic_control = Some(control2);
+				}
+				_ => break,
+			},
+			'S' => match (parser.point(), parser.point()) {
+				(Some(control2), Some(point)) => {
+					let control1 = reflect(previous_cubic_control, current);
+					let control2 = control2.shifted(origin.x, origin.y);
+					current = point.shifted(origin.x, origin.y);
+					path.cubic_to(control1, control2, current);
+					cubic_control = Some(control2);
+				}
+				_ => break,
+			},
+			'Q' => match (parser.point(), parser.point()) {
+				(Some(control), Some(point)) => {
+					let control = control.shifted(origin.x, origin.y);
+					current = point.shifted(origin.x, origin.y);
+					path.quad_to(control, current);
+					quad_control = Some(control);
+				}
+				_ => break,
+			},
+			'T' => match parser.point() {
+				Some(point) => {
+					let control = reflect(previous_quad_control, current);
+					current = point.shifted(origin.x, origin.y);
+					path.quad_to(control, current);
+					quad_control = Some(control);
+				}
+				None => break,
+			},
+			'A' => {
+				let radius_x = parser.number();
+				let radius_y = parser.number();
+				let rotation = parser.number();
+				let large_arc = parser.flag();
+				let sweep = parser.flag();
+				let point = parser.point();
+				match (radius_x, radius_y, rotation, large_arc, sweep, point) {
+					(
+						Some(radius_x),
+						Some(radius_y),
+						Some(rotation),
+						Some(large_arc),
+						Some(sweep),
+						Some(point),
+					) => {
+						current = point.shifted(origin.x, origin.y);
+						path.arc_to(
+							radius_x,
+							radius_y,
+							rotation.to_radians(),
+							large_arc,
+							sweep,
+							current,
+						);
+					}
+					_ => break,
+				}
+			}
+			'Z' => {
+				path.close();
+				current = subpath_start;
+			}
+			_ => break,
+		}
+		previous_cubic_control = cubic_control;
+		previous_quad_control = quad_control;
+	}
+	path
+}
+
+/// Reflects the previous curve's control point around the current
+/// point, or returns the current point if there was no previous curve.
+fn reflect(control: Option<Point>, current: Point) -> Point {
+	match control {
+		Some(control) => Point::new(2.0 * current.x - control.x, 2.0 * current.y - control.y),
+		None => current,
+	}
+}
+
+/// Reads numbers separated by whitespace and/or commas, as
+/// used in path data and other SVG attributes.
+struct NumberParser<'a> {
+	text: &'a str,
+	position: usize,
+}
+
+impl<'a> NumberParser<'a> {
+	fn new(text: &'a str) -> Self {
+		Self { text, position: 0 }
+	}
+
+	fn peek(&self) -> Option<char> {
+		self.text[self.position..].chars().next()
+	}
+
+	fn advance(&mut self) {
+		if let Some(c) = self.peek() {
+			self.position += c.len_utf8();
+		}
+	}
+
+	fn skip_separators(&mut self) {
+		while let Some(c) = self.peek() {
+			if c.is_whitespace() || c == ',' {
+				self.advance();
+			} else {
+				break;
+			}
+		}
+	}
+
+	fn number(&mut self) -> Option<f32> {
+		self.skip_separators();
+		let start = self.position;
+		if let Some('+') | Some('-') = self.peek() {
+			self.advance();
+		}
+		// numbers can run into each other, like "0.5.5" (which is
+		// 0.5 and 0.5) or "1-2" (which is 1 and -2)
+		let mut seen_point = false;
+		let mut seen_digit = false;
+		while let Some(c) = self.peek() {
+			if c.is_ascii_digit() {
+				seen_digit = true;
+			} else if c == '.' && !seen_point {
+				seen_point = true;
+			} else {
+				break;
+			}
+			self.advance();
+		}
+		if !seen_digit {
+			self.position = start;
+			return None;
+		}
+		if let Some('e') | Some('E') = self.peek() {
+			let before_exponent = self.position;
+			self.advance();
+			if let Some('+') | Some('-') = self.peek() {
+				self.advance();
+			}
+			let digits_start = self.position;
+			while let Some(c) = self.peek() {
+				if !c.is_ascii_digit() {
+					break;
+				}
+				self.advance();
+			}
+			if self.position == digits_start {
+				self.position = before_exponent;
+			}
+		}
+		match self.text[start..self.position].parse::<f32>() {
+			// numbers too big to fit in an f32 are parsed as infinity
+			Ok(number) if number.is_finite() => Some(number),
+			_ => {
+				self.position = start;
+				None
+			}
+		}
+	}
+
+	fn point(&mut self) -> Option<Point> {
+		let x = self.number()?;
+		let y = self.number()?;
+		Some(Point::new(x, y))
+	}
+
+	/// Reads an arc flag, which is a single "0" or "1" that
+	/// doesn't need to be separated from the next number.
+	fn flag(&mut self) -> Option<bool> {
+		self.skip_separators();
+		let flag = match self.peek()? {
+			'0' => false,
+			'1' => true,
+			_ => return None,
+		};
+		self.advance();
+		Some(flag)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::path::PathCommand;
+
+	fn assert_point(point: Point, x: f32, y: f32) {
+		assert!(
+			(point.x - x).abs() < 0.0001 && (point.y - y).abs() < 0.0001,
+			"expected ({}, {}), got ({}, {})",
+			x,
+			y,
+			point.x,
+			point.y
+		);
+	}
+
+	fn assert_color(color: Option<Color>, red: f32, green: f32, blue: f32) {
+		let color = color.expect("expected a color");
+		assert!((color.red - red).abs() < 0.01);
+		assert!((color.green - green).abs() < 0.01);
+		assert!((color.blue - blue).abs() < 0.01);
+		assert_eq!(color.alpha, 1.0);
+	}
+
+	fn parse_shapes(body: &str) -> Vec<SvgShape> {
+		let svg = format!(
+			r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 10 10">{}</svg>"#,
+			body
+		);
+		Svg::parse(&svg).unwrap().shapes
+	}
+
+	#[test]
+	fn numbers_can_run_into_each_other() {
+		assert_eq!(parse_number_list("0.5.5"), vec![0.5, 0.5]);
+		assert_eq!(parse_number_list("1-2"), vec![1.0, -2.0]);
+		assert_eq!(parse_number_list("-.5,+3 4"), vec![-0.5, 3.0, 4.0]);
+	}
+
+	#[test]
+	fn numbers_can_have_exponents() {
+		assert_eq!(
+			parse_number_list("1e2 2.5E-1 3e+1"),
+			vec![100.0, 0.25, 30.0]
+		);
+		// an "e" without digits after it isn't part of the number
+		let mut parser = NumberParser::new("4e");
+		assert_eq!(parser.number(), Some(4.0));
+		assert_eq!(parser.peek(), Some('e'));
+	}
+
+	#[test]
+	fn numbers_must_be_finite() {
+		assert_eq!(parse_number("nan"), None);
+		assert_eq!(parse_number("inf"), None);
+		assert_eq!(parse_number("-infinity"), None);
+		assert_eq!(parse_number("1e39"), None);
+		assert_eq!(parse_length("1e39px"), None);
+		assert_eq!(parse_number("3e38"), Some(3e38));
+		// the list stops at the first number that can't be parsed
+		assert_eq!(parse_number_list("1 1e39 2"), vec![1.0]);
+		let shapes = parse_shapes(r#"<path d="M0 0L1e39 0L0 1z"/><rect width="1e39" height="1"/>"#);
+		for shape in &shapes {
+			for subpath in shape.path.flatten(0.1) {
+				assert!(subpath
+					.points
+					.iter()
+					.all(|point| point.x.is_finite() && point.y.is_finite()));
+			}
+		}
+	}
+
+	#[test]
+	fn arc_flags_dont_need_separators() {
+		let path = parse_path_data("M0 0a5 5 0 1010 0");
+		match path.commands()[1] {
+			PathCommand::ArcTo(radius_x, radius_y, rotation, large_arc, sweep, point) => {
+				assert_eq!((radius_x, radius_y, rotation), (5.0, 5.0, 0.0));
+				assert!(large_arc);
+				assert!(!sweep);
+				assert_point(point, 10.0, 0.0);
+			}
+			command => panic!("unexpected command {:?}", command),
+		}
+	}
+
+	#[test]
+	fn numbers_after_move_commands_are_lines() {
+		let path = parse_path_data("M1 1 2 2m1 1 3 3");
+		let commands = path.commands();
+		assert_eq!(commands.len(), 4);
+		match (commands[0], commands[1], commands[2], commands[3]) {
+			(
+				PathCommand::MoveTo(a),
+				PathCommand::LineTo(b),
+				PathCommand::MoveTo(c),
+				PathCommand::LineTo(d),
+			) => {
+				assert_point(a, 1.0, 1.0);
+				assert_point(b, 2.0, 2.0);
+				assert_point(c, 3.0, 3.0);
+				assert_point(d, 6.0, 6.0);
+			}
+			_ => panic!("unexpected commands {:?}", commands),
+		}
+	}
+
+	#[test]
+	fn smooth_curves_reflect_the_previous_control_point() {
+		let path = parse_path_data("M0 0C1 1 2 1 3 0S5 -1 6 0");
+		match path.commands()[2] {
+			PathCommand::CubicTo(control1, control2, point) => {
+				assert_point(control1, 4.0, -1.0);
+				assert_point(control2, 5.0, -1.0);
+				assert_point(point, 6.0, 0.0);
+			}
+			command => panic!("unexpected command {:?}", command),
+		}
+		let path = parse_path_data("M0 0Q1 1 2 0t2 0");
+		match path.commands()[2] {
+			PathCommand::QuadTo(control, point) => {
+				assert_point(control, 3.0, -1.0);
+				assert_point(point, 4.0, 0.0);
+			}
+			command => panic!("unexpected command {:?}", command),
+		}
+		// without a previous curve, the control point is the current point
+		let path = parse_path_data("M1 1T3 1");
+		match path.commands()[1] {
+			PathCommand::QuadTo(control, _) => assert_point(control, 1.0, 1.0),
+			command => panic!("unexpected command {:?}", command),
+		}
+	}
+
+	#[test]
+	fn transforms_are_applied_from_right_to_left() {
+		let transform = parse_transform("translate(10 0) scale(2)");
+		assert_point(transform.transform_point(Point::new(1.0, 1.0)), 12.0, 2.0);
+		let transform = parse_transform("scale(2),translate(10)");
+		assert_point(transform.transform_point(Point::new(1.0, 1.0)), 22.0, 2.0);
+		let transform = parse_transform("rotate(90 1 1)");
+		assert_point(transform.transform_point(Point::new(2.0, 1.0)), 1.0, 2.0);
+		// invalid transform lists aren't applied at all
+		let transform = parse_transform("translate(10 0) scale(1 2 3)");
+		assert_point(transform.transform_point(Point::new(1.0, 1.0)), 1.0, 1.0);
+	}
+
+	#[test]
+	fn style_attribute_overrides_presentation_attributes() {
+		let shapes = parse_shapes(
+			r#"<g fill="red"><rect width="1" height="1" fill="lime" style="fill: blue; fill-rule: evenodd"/></g>"#,
+		);
+		assert_eq!(shapes.len(), 1);
+		match shapes[0].fill.map(|paint| paint.color) {
+			Some(SvgColor::Color(color)) => assert_color(Some(color), 0.0, 0.0, 1.0),
+			_ => panic!("expected a fill color"),
+		}
+		assert_eq!(shapes[0].path.fill_rule(), FillRule::EvenOdd);
+	}
+
+	#[test]
+	fn hidden_elements_are_skipped() {
+		let shapes = parse_shapes(
+			r#"<rect width="1" height="1" display="none"/>
+			<g style="display:none"><rect width="1" height="1"/></g>
+			<rect width="1" height="1"/>"#,
+		);
+		assert_eq!(shapes.len(), 1);
+	}
+
+	#[test]
+	fn parses_colors() {
+		assert_color(parse_color("#f80"), 1.0, 0.533, 0.0);
+		assert_color(parse_color("#00ff80"), 0.0, 1.0, 0.502);
+		assert_color(parse_color("rgb(255, 0, 51)"), 1.0, 0.0, 0.2);
+		assert_color(parse_color("rgb(100%,50%,0%)"), 1.0, 0.5, 0.0);
+		assert_color(parse_color("navy"), 0.0, 0.0, 0.502);
+		assert!(parse_color("#ff").is_none());
+		assert!(parse_color("#ggg").is_none());
+		assert!(parse_color("rgb(1, 2)").is_none());
+	}
+}
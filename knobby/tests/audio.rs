@@ -0,0 +1,269 @@
+use knobby::{
+	audio::{AudioBridgeSettings, OverflowPolicy},
+	behavior::Behavior,
+	control::ControlSettings,
+	event::Event,
+	geometry::Rectangle,
+	gui::{Controls, EventQueue, Gui, Resources},
+	parameter::Parameters,
+};
+use std::{cell::RefCell, rc::Rc, thread};
+
+type ReceivedEvents = Rc<RefCell<Vec<Event<u32>>>>;
+
+/// Sends custom events emitted in the GUI to the audio thread
+/// and records the parameter changes the audio thread sends back.
+struct Forwarder {
+	received: ReceivedEvents,
+}
+
+impl Behavior<u32> for Forwarder {
+	fn on(
+		&mut self,
+		event: Event<u32>,
+		_controls: &mut Controls,
+		_resources: &Resources,
+		_parameters: &Parameters,
+		event_queue: &mut EventQueue<u32>,
+	) {
+		match event {
+			Event::Custom(_) | Event::BeginParameterGesture(_) | Event::EndParameterGesture(_) => {
+				event_queue.push(event)
+			}
+			Event::SetParameter(_, _) => self.received.borrow_mut().push(event),
+			_ => {}
+		}
+	}
+}
+
+/// Records every parameter change and custom event it receives.
+struct Recorder {
+	received: ReceivedEvents,
+}
+
+impl Behavior<u32> for Recorder {
+	fn on(
+		&mut self,
+		event: Event<u32>,
+		_controls: &mut Controls,
+		_resources: &Resources,
+		_parameters: &Parameters,
+		_event_queue: &mut EventQueue<u32>,
+	) {
+		if let Event::SetParameter(_, _) | Event::Custom(_) = event {
+			self.received.borrow_mut().push(event);
+		}
+	}
+}
+
+fn create_gui() -> (Gui<u32>, ReceivedEvents) {
+	let mut gui = Gui::new();
+	let received = Rc::new(RefCell::new(vec![]));
+	gui.add_control(
+		ControlSettings {
+			rectangle: Rectangle::new(0.0, 0.0, 10.0, 10.0),
+			height: 0,
+			parent: None,
+			visible: true,
+			focusable: false,
+		},
+		vec![Box::new(Forwarder {
+			received: received.clone(),
+		})],
+	);
+	(gui, received)
+}
+
+#[test]
+fn events_cross_threads_in_order() {
+	const COUNT: u32 = 10_000;
+	let (mut gui, received) = create_gui();
+	// the queues are big enough to hold every event,
+	// so nothing should be dropped
+	let mut handle = gui.connect_audio(AudioBridgeSettings {
+		capacity: COUNT as usize,
+		gui_to_audio_policy: OverflowPolicy::DropNewest,
+		audio_to_gui_policy: OverflowPolicy::DropNewest,
+	});
+	let audio_thread = thread::spawn(move || {
+		let mut next_expected = 0;
+		let mut next_to_send = 0;
+		while next_expected < COUNT || next_to_send < COUNT {
+			while let Some(event) = handle.receive() {
+				match event {
+					Event::Custom(value) => {
+						assert_eq!(value, next_expected);
+						next_expected += 1;
+					}
+					_ => panic!("unexpected event"),
+				}
+			}
+			if next_to_send < COUNT {
+				assert!(handle.send(Event::SetParameter(0, next_to_send as f32)));
+				next_to_send += 1;
+			}
+		}
+	});
+	let mut next_to_emit = 0;
+	while next_to_emit < COUNT || received.borrow().len() < COUNT as usize {
+		for _ in 0..32 {
+			if next_to_emit < COUNT {
+				gui.emit(Event::Custom(next_to_emit), None);
+				next_to_emit += 1;
+			}
+		}
+		gui.sync_audio();
+		thread::yield_now();
+	}
+	audio_thread.join().unwrap();
+	for (i, event) in received.borrow().iter().enumerate() {
+		match event {
+			Event::SetParameter(0, value) => assert_eq!(*value, i as f32),
+			_ => panic!("unexpected event"),
+		}
+	}
+}
+
+#[test]
+fn drop_oldest_keeps_newest_events() {
+	let (mut gui, _) = create_gui();
+	let mut handle = gui.connect_audio(AudioBridgeSettings {
+		capacity: 4,
+		gui_to_audio_policy: OverflowPolicy::DropOldest,
+		audio_to_gui_policy: OverflowPolicy::DropOldest,
+	});
+	for i in 0..10 {
+		gui.emit(Event::Custom(i), None);
+	}
+	gui.sync_audio();
+	let mut values = vec![];
+	while let Some(Event::Custom(value)) = handle.receive() {
+		values.push(value);
+	}
+	assert_eq!(values, vec![6, 7, 8, 9]);
+}
+
+#[test]
+fn gestures_stay_balanced_when_the_queue_overflows() {
+	let (mut gui, _) = create_gui();
+	let mut handle = gui.connect_audio(AudioBridgeSettings {
+		capacity: 4,
+		gui_to_audio_policy: OverflowPolicy::DropOldest,
+		audio_to_gui_policy: OverflowPolicy::DropOldest,
+	});
+	for index in 0..2 {
+		gui.emit(Event::BeginParameterGesture(index), None);
+		for i in 0..10 {
+			gui.emit(Event::Custom(i), None);
+		}
+		gui.emit(Event::EndParameterGesture(index), None);
+	}
+	// the audio thread only catches up after a few frames
+	let mut gestures = vec![];
+	for _ in 0..4 {
+		gui.sync_audio();
+		while let Some(event) = handle.receive() {
+			match event {
+				Event::BeginParameterGesture(index) => gestures.push((true, index)),
+				Event::EndParameterGesture(index) => gestures.push((false, index)),
+				_ => {}
+			}
+		}
+	}
+	assert_eq!(gestures, vec![(true, 0), (false, 0), (true, 1), (false, 1)]);
+}
+
+#[test]
+fn coalesced_parameters_keep_latest_value() {
+	let (mut gui, received) = create_gui();
+	let mut handle = gui.connect_audio(AudioBridgeSettings {
+		capacity: 4,
+		gui_to_audio_policy: OverflowPolicy::DropNewest,
+		audio_to_gui_policy: OverflowPolicy::CoalesceParameters(2),
+	});
+	for i in 0..100 {
+		assert!(handle.send(Event::SetParameter(0, i as f32)));
+		assert!(handle.send(Event::SetParameter(1, -(i as f32))));
+	}
+	gui.sync_audio();
+	let received = received.borrow();
+	assert_eq!(received.len(), 2);
+	match (received[0], received[1]) {
+		(Event::SetParameter(0, first), Event::SetParameter(1, second)) => {
+			assert_eq!(first, 99.0);
+			assert_eq!(second, -99.0);
+		}
+		_ => panic!("unexpected events"),
+	}
+}
+
+#[test]
+fn drop_oldest_keeps_events_in_order_across_threads() {
+	const COUNT: u32 = 100_000;
+	let (mut gui, _) = create_gui();
+	let mut handle = gui.connect_audio(AudioBridgeSettings {
+		capacity: 8,
+		gui_to_audio_policy: OverflowPolicy::DropOldest,
+		audio_to_gui_policy: OverflowPolicy::DropOldest,
+	});
+	let audio_thread = thread::spawn(move || {
+		// events may be dropped, but the ones that arrive should
+		// never be repeated or out of order
+		let mut previous = None;
+		while previous != Some(COUNT - 1) {
+			while let Some(event) = handle.receive() {
+				match event {
+					Event::Custom(value) => {
+						if let Some(previous) = previous {
+							assert!(value > previous);
+						}
+						previous = Some(value);
+					}
+					_ => panic!("unexpected event"),
+				}
+			}
+		}
+	});
+	for i in 0..COUNT {
+		gui.emit(Event::Custom(i), None);
+		gui.sync_audio();
+	}
+	audio_thread.join().unwrap();
+}
+
+#[test]
+fn coalesced_parameters_stay_in_order_with_other_events() {
+	let mut gui = Gui::new();
+	let received = Rc::new(RefCell::new(vec![]));
+	gui.add_control(
+		ControlSettings {
+			rectangle: Rectangle::new(0.0, 0.0, 10.0, 10.0),
+			height: 0,
+			parent: None,
+			visible: true,
+			focusable: false,
+		},
+		vec![Box::new(Recorder {
+			received: received.clone(),
+		})],
+	);
+	let mut handle = gui.connect_audio(AudioBridgeSettings {
+		capacity: 4,
+		gui_to_audio_policy: OverflowPolicy::DropNewest,
+		audio_to_gui_policy: OverflowPolicy::CoalesceParameters(1),
+	});
+	assert!(handle.send(Event::SetParameter(0, 1.0)));
+	assert!(handle.send(Event::Custom(7)));
+	assert!(handle.send(Event::SetParameter(0, 2.0)));
+	assert!(handle.send(Event::SetParameter(0, 3.0)));
+	gui.sync_audio();
+	let received = received.borrow();
+	assert_eq!(received.len(), 3);
+	match (received[0], received[1], received[2]) {
+		(Event::SetParameter(0, first), Event::Custom(7), Event::SetParameter(0, second)) => {
+			assert_eq!(first, 1.0);
+			assert_eq!(second, 3.0);
+		}
+		_ => panic!("unexpected events"),
+	}
+}
@@ -0,0 +1,681 @@
+use knobby::{
+	animation::{Easing, Tween},
+	behavior::Behavior,
+	canvas::{Canvas, Color, DrawMode, DrawOperation, Paint, Style},
+	control::{Control, ControlSettings},
+	event::Event,
+	geometry::Rectangle,
+	gui::{ControlId, Controls, EventQueue, Gui, Resources},
+	input::{Modifiers, MouseButton, ScrollUnit},
+	meter::{MeterLayout, Meters},
+	parameter::Parameters,
+};
+use std::{cell::RefCell, rc::Rc};
+
+/// Changes a parameter when it's scrolled, without ever
+/// ending the gesture itself.
+struct ScrollKnob;
+
+impl Behavior<()> for ScrollKnob {
+	fn on(
+		&mut self,
+		event: Event<()>,
+		_controls: &mut Controls,
+		_resources: &Resources,
+		_parameters: &Parameters,
+		event_queue: &mut EventQueue<()>,
+	) {
+		if let Event::Scroll(_, _, dy, _, _) = event {
+			event_queue.push(Event::BeginParameterGesture(0));
+			event_queue.push(Event::SetParameter(0, dy));
+		}
+	}
+}
+
+fn event_names(events: Vec<Event<()>>) -> Vec<&'static str> {
+	events
+		.into_iter()
+		.map(|event| match event {
+			Event::BeginParameterGesture(_) => "begin",
+			Event::SetParameter(_, _) => "set",
+			Event::EndParameterGesture(_) => "end",
+			_ => "other",
+		})
+		.collect()
+}
+
+/// Records the names of the hover, hold, and focus events it receives.
+struct InteractionRecorder {
+	received: Rc<RefCell<Vec<&'static str>>>,
+}
+
+impl Behavior<()> for InteractionRecorder {
+	fn on(
+		&mut self,
+		event: Event<()>,
+		_controls: &mut Controls,
+		_resources: &Resources,
+		_parameters: &Parameters,
+		_event_queue: &mut EventQueue<()>,
+	) {
+		let name = match event {
+			Event::Hover(..) => "hover",
+			Event::Unhover(..) => "unhover",
+			Event::Press(..) => "press",
+			Event::Release(..) => "release",
+			Event::Click(..) => "click",
+			Event::Focus(..) => "focus",
+			Event::Blur(..) => "blur",
+			_ => return,
+		};
+		self.received.borrow_mut().push(name);
+	}
+}
+
+/// Records the names of the events it receives along with
+/// the IDs of the controls they're for.
+struct LifecycleRecorder {
+	received: Rc<RefCell<Vec<(&'static str, ControlId)>>>,
+}
+
+impl Behavior<()> for LifecycleRecorder {
+	fn on(
+		&mut self,
+		event: Event<()>,
+		_controls: &mut Controls,
+		_resources: &Resources,
+		_parameters: &Parameters,
+		_event_queue: &mut EventQueue<()>,
+	) {
+		let received = match event {
+			Event::Added(id) => ("added", id),
+			Event::Removed(id) => ("removed", id),
+			Event::Hover(id, ..) => ("hover", id),
+			Event::Unhover(id) => ("unhover", id),
+			Event::Press(id, ..) => ("press", id),
+			Event::Release(id, ..) => ("release", id),
+			Event::Click(id, ..) => ("click", id),
+			Event::Focus(id) => ("focus", id),
+			Event::Blur(id) => ("blur", id),
+			_ => return,
+		};
+		self.received.borrow_mut().push(received);
+	}
+}
+
+/// Fills its control's rectangle.
+struct Fill;
+
+impl Behavior<()> for Fill {
+	fn draw(
+		&self,
+		control: &Control,
+		_resources: &Resources,
+		_parameters: &Parameters,
+		_meters: &Meters,
+		canvas: &mut Canvas,
+	) {
+		canvas.draw_rectangle(
+			control.absolute_rectangle,
+			Style {
+				mode: DrawMode::Fill,
+				paint: Paint::Solid(Color::new(1.0, 1.0, 1.0, 1.0)),
+			},
+		);
+	}
+}
+
+/// Records the click count of every click it receives.
+struct ClickCounter {
+	clicks: Rc<RefCell<Vec<u32>>>,
+}
+
+impl Behavior<()> for ClickCounter {
+	fn on(
+		&mut self,
+		event: Event<()>,
+		_controls: &mut Controls,
+		_resources: &Resources,
+		_parameters: &Parameters,
+		_event_queue: &mut EventQueue<()>,
+	) {
+		if let Event::Click(_, _, _, _, _, click_count) = event {
+			self.clicks.borrow_mut().push(click_count);
+		}
+	}
+}
+
+/// Pushes the parameter and custom events it receives
+/// to the event queue.
+struct Forwarder;
+
+impl Behavior<()> for Forwarder {
+	fn on(
+		&mut self,
+		event: Event<()>,
+		_controls: &mut Controls,
+		_resources: &Resources,
+		_parameters: &Parameters,
+		event_queue: &mut EventQueue<()>,
+	) {
+		if let Event::BeginParameterGesture(_)
+		| Event::SetParameter(_, _)
+		| Event::EndParameterGesture(_)
+		| Event::Custom(_) = event
+		{
+			event_queue.push(event);
+		}
+	}
+}
+
+#[test]
+fn moving_a_dirty_control_invalidates_its_old_area() {
+	let mut gui: Gui<()> = Gui::new();
+	let old_rectangle = Rectangle::new(10.0, 10.0, 20.0, 20.0);
+	let id = gui
+		.add_control(
+			ControlSettings {
+				rectangle: old_rectangle,
+				height: 0,
+				parent: None,
+				visible: true,
+				focusable: false,
+			},
+			vec![],
+		)
+		.unwrap();
+	let mut layout = MeterLayout::new();
+	layout.add_value("level");
+	let mut publisher = gui.set_meter_layout(layout);
+	gui.draw(&mut Canvas::new());
+	// new meter values mark every control as dirty...
+	let meter_id = publisher.find("level").unwrap();
+	publisher.set(meter_id, 0.5);
+	gui.update(0.0);
+	// ...but moving one afterwards should still remember where it was
+	gui.controls.get_mut(&id).unwrap().rectangle = Rectangle::new(50.0, 50.0, 20.0, 20.0);
+	let regions = gui.invalidated_regions().unwrap();
+	assert!(regions.iter().any(|region| region.x == old_rectangle.x
+		&& region.y == old_rectangle.y
+		&& region.width == old_rectangle.width
+		&& region.height == old_rectangle.height));
+}
+
+#[test]
+fn running_animations_keep_their_control_dirty() {
+	let mut gui: Gui<()> = Gui::new();
+	let id = gui
+		.add_control(
+			ControlSettings {
+				rectangle: Rectangle::new(0.0, 0.0, 10.0, 10.0),
+				height: 0,
+				parent: None,
+				visible: true,
+				focusable: false,
+			},
+			vec![],
+		)
+		.unwrap();
+	gui.draw(&mut Canvas::new());
+	assert!(!gui.needs_redraw());
+	let mut tween = Tween::new(0.0);
+	tween.animate_to(1.0, 1.0, Easing::Linear);
+	assert!(gui.controls.start_animation(&id, "fade", tween));
+	assert!(!gui.controls.start_animation(&(id + 1), "fade", tween));
+	for _ in 0..2 {
+		gui.update(0.25);
+		assert!(gui.needs_redraw());
+		gui.draw(&mut Canvas::new());
+	}
+	let control = gui.controls.get(&id).unwrap();
+	assert_eq!(control.animation_value("fade"), Some(0.5));
+	assert_eq!(control.animation_value::<f32>("missing"), None);
+	// the control is redrawn once more after the animation finishes
+	// so its final value is shown
+	gui.update(0.5);
+	assert!(!gui.is_animating());
+	assert!(gui.needs_redraw());
+	gui.draw(&mut Canvas::new());
+	gui.update(0.5);
+	assert!(!gui.needs_redraw());
+	assert_eq!(
+		gui.controls.get(&id).unwrap().animation_value("fade"),
+		Some(1.0)
+	);
+}
+
+#[test]
+fn colors_and_rectangles_can_be_animated() {
+	let mut gui: Gui<()> = Gui::new();
+	let id = gui
+		.add_control(
+			ControlSettings {
+				rectangle: Rectangle::new(0.0, 0.0, 10.0, 10.0),
+				height: 0,
+				parent: None,
+				visible: true,
+				focusable: false,
+			},
+			vec![],
+		)
+		.unwrap();
+	let mut color = Tween::new(Color::new(0.0, 0.0, 0.0, 1.0));
+	color.animate_to(Color::new(1.0, 0.5, 0.0, 1.0), 1.0, Easing::Linear);
+	let mut bounds = Tween::new(Rectangle::new(0.0, 0.0, 10.0, 10.0));
+	bounds.animate_to(Rectangle::new(10.0, 0.0, 20.0, 10.0), 1.0, Easing::Linear);
+	assert!(gui.controls.start_animation(&id, "color", color));
+	assert!(gui.controls.start_animation(&id, "bounds", bounds));
+	gui.update(0.5);
+	assert!(gui.is_animating());
+	let control = gui.controls.get(&id).unwrap();
+	let color: Color = control.animation_value("color").unwrap();
+	assert_eq!((color.red, color.green, color.blue), (0.5, 0.25, 0.0));
+	let bounds: Rectangle = control.animation_value("bounds").unwrap();
+	assert_eq!((bounds.x, bounds.width), (5.0, 15.0));
+	// asking for a different type of value than the animation has
+	// doesn't find anything
+	assert_eq!(control.animation_value::<f32>("color"), None);
+	gui.update(0.5);
+	assert!(!gui.is_animating());
+}
+
+#[test]
+fn controls_need_their_parent_to_be_added_first() {
+	let mut gui: Gui<()> = Gui::new();
+	let settings = |parent| ControlSettings {
+		rectangle: Rectangle::new(0.0, 0.0, 10.0, 10.0),
+		height: 0,
+		parent,
+		visible: true,
+		focusable: false,
+	};
+	assert_eq!(gui.add_control(settings(Some(5)), vec![]), None);
+	let parent = gui.add_control(settings(None), vec![]).unwrap();
+	let child = gui.add_control(settings(Some(parent)), vec![]).unwrap();
+	assert_eq!(gui.controls.get(&child).unwrap().parent(), Some(parent));
+}
+
+#[test]
+fn hiding_a_control_forgets_that_it_was_hovered_held_and_focused() {
+	let mut gui: Gui<()> = Gui::new();
+	let received = Rc::new(RefCell::new(vec![]));
+	let parent = gui
+		.add_control(
+			ControlSettings {
+				rectangle: Rectangle::new(0.0, 0.0, 100.0, 100.0),
+				height: 0,
+				parent: None,
+				visible: true,
+				focusable: false,
+			},
+			vec![],
+		)
+		.unwrap();
+	let child = gui
+		.add_control(
+			ControlSettings {
+				rectangle: Rectangle::new(10.0, 10.0, 20.0, 20.0),
+				height: 0,
+				parent: Some(parent),
+				visible: true,
+				focusable: true,
+			},
+			vec![Box::new(InteractionRecorder {
+				received: received.clone(),
+			})],
+		)
+		.unwrap();
+	gui.on_mouse_move(15.0, 15.0, 0.0, 0.0, Modifiers::new());
+	gui.on_mouse_down(MouseButton::Left, 15.0, 15.0, Modifiers::new(), 0.0);
+	assert_eq!(*received.borrow(), vec!["hover", "focus", "press"]);
+	received.borrow_mut().clear();
+	// hiding the parent hides the child as well
+	gui.controls.get_mut(&parent).unwrap().visible = false;
+	gui.update(0.0);
+	assert_eq!(*received.borrow(), vec!["unhover", "release", "blur"]);
+	let control = gui.controls.get(&child).unwrap();
+	assert!(!control.is_hovered);
+	assert!(!control.is_held[MouseButton::Left]);
+	assert!(!control.is_focused);
+	// releasing the mouse button afterwards doesn't click the control
+	received.borrow_mut().clear();
+	gui.on_mouse_up(MouseButton::Left, 15.0, 15.0, Modifiers::new());
+	assert!(received.borrow().is_empty());
+}
+
+#[test]
+fn removing_a_control_removes_its_descendants_and_forgets_them() {
+	let mut gui: Gui<()> = Gui::new();
+	let received = Rc::new(RefCell::new(vec![]));
+	let add = |gui: &mut Gui<()>, rectangle, height, parent| {
+		gui.add_control(
+			ControlSettings {
+				rectangle,
+				height,
+				parent,
+				visible: true,
+				focusable: true,
+			},
+			vec![Box::new(LifecycleRecorder {
+				received: received.clone(),
+			})],
+		)
+		.unwrap()
+	};
+	// a control underneath the ones that will be removed
+	let sibling = add(&mut gui, Rectangle::new(0.0, 0.0, 100.0, 100.0), 0, None);
+	let parent = add(&mut gui, Rectangle::new(0.0, 0.0, 50.0, 50.0), 1, None);
+	let child = add(
+		&mut gui,
+		Rectangle::new(10.0, 10.0, 20.0, 20.0),
+		0,
+		Some(parent),
+	);
+	let grandchild = add(&mut gui, Rectangle::new(0.0, 0.0, 5.0, 5.0), 0, Some(child));
+	assert_eq!(
+		received.replace(vec![]),
+		vec![
+			("added", sibling),
+			("added", parent),
+			("added", child),
+			("added", grandchild)
+		]
+	);
+	gui.on_mouse_move(12.0, 12.0, 0.0, 0.0, Modifiers::new());
+	gui.on_mouse_down(MouseButton::Left, 12.0, 12.0, Modifiers::new(), 0.0);
+	assert_eq!(
+		received.replace(vec![]),
+		vec![
+			("hover", grandchild),
+			("focus", grandchild),
+			("press", grandchild)
+		]
+	);
+	// descendants are removed before their ancestors
+	assert!(gui.remove_control(parent));
+	assert_eq!(
+		received.replace(vec![]),
+		vec![
+			("removed", grandchild),
+			("removed", child),
+			("removed", parent)
+		]
+	);
+	for id in &[parent, child, grandchild] {
+		assert!(gui.controls.get(id).is_none());
+	}
+	assert!(!gui.remove_control(parent));
+	assert!(gui
+		.add_control(
+			ControlSettings {
+				rectangle: Rectangle::new(0.0, 0.0, 10.0, 10.0),
+				height: 0,
+				parent: Some(child),
+				visible: true,
+				focusable: false,
+			},
+			vec![],
+		)
+		.is_none());
+	// releasing the mouse button doesn't release or click anything,
+	// and the control underneath is hovered and focused as usual
+	gui.on_mouse_up(MouseButton::Left, 12.0, 12.0, Modifiers::new());
+	assert!(received.borrow().is_empty());
+	gui.on_mouse_move(13.0, 13.0, 1.0, 1.0, Modifiers::new());
+	gui.on_mouse_down(MouseButton::Left, 13.0, 13.0, Modifiers::new(), 1.0);
+	gui.on_mouse_up(MouseButton::Left, 13.0, 13.0, Modifiers::new());
+	assert_eq!(
+		received.replace(vec![]),
+		vec![
+			("hover", sibling),
+			("focus", sibling),
+			("press", sibling),
+			("release", sibling),
+			("click", sibling)
+		]
+	);
+	let control = gui.controls.get(&sibling).unwrap();
+	assert!(control.is_hovered);
+	assert!(control.is_focused);
+}
+
+#[test]
+fn gestures_started_by_scrolling_end_after_the_scroll() {
+	let mut gui: Gui<()> = Gui::new();
+	gui.add_control(
+		ControlSettings {
+			rectangle: Rectangle::new(0.0, 0.0, 10.0, 10.0),
+			height: 0,
+			parent: None,
+			visible: true,
+			focusable: false,
+		},
+		vec![Box::new(ScrollKnob)],
+	)
+	.unwrap();
+	gui.on_scroll(5.0, 5.0, 0.0, 1.0, ScrollUnit::Lines, Modifiers::new());
+	assert_eq!(event_names(gui.drain_events()), vec!["begin", "set", "end"]);
+	// if the control is held down, the gesture lasts until it's released
+	gui.on_mouse_move(5.0, 5.0, 0.0, 0.0, Modifiers::new());
+	gui.on_mouse_down(MouseButton::Left, 5.0, 5.0, Modifiers::new(), 0.0);
+	gui.on_scroll(5.0, 5.0, 0.0, 1.0, ScrollUnit::Lines, Modifiers::new());
+	gui.on_scroll(5.0, 5.0, 0.0, 2.0, ScrollUnit::Lines, Modifiers::new());
+	assert_eq!(event_names(gui.drain_events()), vec!["begin", "set", "set"]);
+	gui.on_mouse_up(MouseButton::Left, 5.0, 5.0, Modifiers::new());
+	assert_eq!(event_names(gui.drain_events()), vec!["end"]);
+}
+
+#[test]
+fn only_visible_focusable_controls_can_be_focused() {
+	let mut gui: Gui<()> = Gui::new();
+	let settings = |visible, focusable| ControlSettings {
+		rectangle: Rectangle::new(0.0, 0.0, 10.0, 10.0),
+		height: 0,
+		parent: None,
+		visible,
+		focusable,
+	};
+	let focusable = gui.add_control(settings(true, true), vec![]).unwrap();
+	let not_focusable = gui.add_control(settings(true, false), vec![]).unwrap();
+	let hidden = gui.add_control(settings(false, true), vec![]).unwrap();
+	assert!(gui.focus(Some(focusable)));
+	assert!(!gui.focus(Some(not_focusable)));
+	assert!(!gui.focus(Some(hidden)));
+	assert!(!gui.focus(Some(hidden + 1)));
+	// the focus stays on the last control that could be focused
+	assert!(gui.controls.get(&focusable).unwrap().is_focused);
+	assert!(gui.focus(None));
+	assert!(!gui.controls.get(&focusable).unwrap().is_focused);
+}
+
+#[test]
+fn repeated_parameter_changes_are_coalesced() {
+	let mut gui: Gui<()> = Gui::new();
+	let id = gui
+		.add_control(
+			ControlSettings {
+				rectangle: Rectangle::new(0.0, 0.0, 10.0, 10.0),
+				height: 0,
+				parent: None,
+				visible: true,
+				focusable: false,
+			},
+			vec![Box::new(Forwarder)],
+		)
+		.unwrap();
+	let send = |gui: &mut Gui<()>, events: &[Event<()>]| {
+		for event in events {
+			gui.emit(*event, Some(id));
+		}
+		gui.drain_events()
+			.into_iter()
+			.map(|event| match event {
+				Event::BeginParameterGesture(index) => format!("begin {}", index),
+				Event::SetParameter(index, value) => format!("set {} {}", index, value),
+				Event::EndParameterGesture(index) => format!("end {}", index),
+				_ => "custom".to_string(),
+			})
+			.collect::<Vec<_>>()
+	};
+	// nothing is coalesced unless it's enabled
+	assert_eq!(
+		send(
+			&mut gui,
+			&[Event::SetParameter(0, 0.1), Event::SetParameter(0, 0.2)]
+		),
+		vec!["set 0 0.1", "set 0 0.2"]
+	);
+	gui.set_parameter_coalescing(true);
+	// changes to the same parameter collapse into the latest value,
+	// even with changes to other parameters in between
+	assert_eq!(
+		send(
+			&mut gui,
+			&[
+				Event::SetParameter(0, 0.1),
+				Event::SetParameter(1, 0.5),
+				Event::SetParameter(0, 0.2),
+				Event::SetParameter(0, 0.3),
+			]
+		),
+		vec!["set 0 0.3", "set 1 0.5"]
+	);
+	// but never across gestures or custom events
+	assert_eq!(
+		send(
+			&mut gui,
+			&[
+				Event::SetParameter(0, 0.1),
+				Event::BeginParameterGesture(0),
+				Event::SetParameter(0, 0.2),
+				Event::SetParameter(0, 0.3),
+				Event::EndParameterGesture(0),
+				Event::SetParameter(0, 0.4),
+				Event::Custom(()),
+				Event::SetParameter(0, 0.5),
+				Event::SetParameter(0, 0.6),
+			]
+		),
+		vec![
+			"set 0 0.1",
+			"begin 0",
+			"set 0 0.3",
+			"end 0",
+			"set 0 0.4",
+			"custom",
+			"set 0 0.6",
+		]
+	);
+	// and only events that are still queued are changed
+	gui.emit(Event::SetParameter(0, 0.1), Some(id));
+	assert_eq!(gui.drain_events().len(), 1);
+	assert_eq!(
+		send(&mut gui, &[Event::SetParameter(0, 0.2)]),
+		vec!["set 0 0.2"]
+	);
+}
+
+#[test]
+fn clicks_in_quick_succession_are_counted() {
+	let mut gui: Gui<()> = Gui::new();
+	gui.double_click_interval = 0.5;
+	gui.double_click_distance = 4.0;
+	let clicks = Rc::new(RefCell::new(vec![]));
+	gui.add_control(
+		ControlSettings {
+			rectangle: Rectangle::new(0.0, 0.0, 20.0, 20.0),
+			height: 0,
+			parent: None,
+			visible: true,
+			focusable: false,
+		},
+		vec![Box::new(ClickCounter {
+			clicks: clicks.clone(),
+		})],
+	)
+	.unwrap();
+	let click = |gui: &mut Gui<()>, x: f32, time: f64| {
+		gui.on_mouse_move(x, 5.0, 0.0, 0.0, Modifiers::new());
+		gui.on_mouse_down(MouseButton::Left, x, 5.0, Modifiers::new(), time);
+		gui.on_mouse_up(MouseButton::Left, x, 5.0, Modifiers::new());
+	};
+	// clicks only count as part of a series if they're close enough
+	// together in time...
+	click(&mut gui, 5.0, 0.0);
+	click(&mut gui, 5.0, 0.5);
+	click(&mut gui, 5.0, 0.75);
+	click(&mut gui, 5.0, 1.5);
+	assert_eq!(clicks.replace(vec![]), vec![1, 2, 3, 1]);
+	// ...and in space
+	click(&mut gui, 5.0, 3.0);
+	click(&mut gui, 9.0, 3.1);
+	click(&mut gui, 13.5, 3.2);
+	assert_eq!(clicks.replace(vec![]), vec![1, 2, 1]);
+	// a press that's released outside of the control doesn't click it,
+	// and the next press starts a new series
+	click(&mut gui, 5.0, 5.0);
+	gui.on_mouse_down(MouseButton::Left, 5.0, 5.0, Modifiers::new(), 5.1);
+	gui.on_mouse_move(30.0, 5.0, 25.0, 0.0, Modifiers::new());
+	gui.on_mouse_up(MouseButton::Left, 30.0, 5.0, Modifiers::new());
+	click(&mut gui, 5.0, 5.2);
+	assert_eq!(clicks.replace(vec![]), vec![1, 1]);
+	// the same goes for presses that are cancelled
+	click(&mut gui, 5.0, 7.0);
+	gui.on_mouse_down(MouseButton::Left, 5.0, 5.0, Modifiers::new(), 7.1);
+	gui.cancel_mouse_input();
+	click(&mut gui, 5.0, 7.2);
+	assert_eq!(clicks.replace(vec![]), vec![1, 1]);
+}
+
+#[test]
+fn higher_controls_are_hovered_and_drawn_on_top() {
+	let mut gui: Gui<()> = Gui::new();
+	// three overlapping controls, where the first and last
+	// have the same height
+	let mut add = |x, height| {
+		gui.add_control(
+			ControlSettings {
+				rectangle: Rectangle::new(x, 0.0, 20.0, 20.0),
+				height,
+				parent: None,
+				visible: true,
+				focusable: false,
+			},
+			vec![Box::new(Fill)],
+		)
+		.unwrap()
+	};
+	let first = add(0.0, 1);
+	let lower = add(5.0, 0);
+	let last = add(10.0, 1);
+	let drawn_x = |gui: &mut Gui<()>| {
+		gui.update_draw_cache();
+		gui.draw_operations()
+			.map(|operation| match operation {
+				DrawOperation::Rectangle(rectangle, _) => rectangle.x,
+				_ => panic!("only rectangles should be drawn"),
+			})
+			.collect::<Vec<_>>()
+	};
+	// lower controls are drawn first, and controls with the same height
+	// are drawn in the order they were added
+	assert_eq!(drawn_x(&mut gui), vec![5.0, 0.0, 10.0]);
+	let hovered = |gui: &mut Gui<()>, x| {
+		gui.on_mouse_move(x, 5.0, 0.0, 0.0, Modifiers::new());
+		[first, lower, last]
+			.iter()
+			.copied()
+			.filter(|id| gui.controls.get(id).unwrap().is_hovered)
+			.collect::<Vec<_>>()
+	};
+	// the control drawn on top is the one that's hovered
+	assert_eq!(hovered(&mut gui, 15.0), vec![last]);
+	assert_eq!(hovered(&mut gui, 7.0), vec![first]);
+	assert_eq!(hovered(&mut gui, 22.0), vec![last]);
+	assert_eq!(hovered(&mut gui, 27.0), vec![last]);
+	assert_eq!(hovered(&mut gui, 40.0), vec![]);
+	// changing a control's height moves it
+	gui.controls.get_mut(&lower).unwrap().height = 2;
+	assert_eq!(drawn_x(&mut gui), vec![0.0, 10.0, 5.0]);
+	assert_eq!(hovered(&mut gui, 15.0), vec![lower]);
+}
@@ -0,0 +1,189 @@
+use knobby::geometry::{Point, Rectangle, Transform};
+
+/// How much of something is inside a clip rectangle.
+pub(crate) enum Coverage {
+	Inside,
+	Outside,
+	Partial,
+}
+
+/// Returns how much of a rectangle is inside the clip rectangle once
+/// the transform is applied to it.
+///
+/// Rotated rectangles are compared using their bounding boxes, so they
+/// may be reported as partially inside when they're actually outside.
+pub(crate) fn coverage(
+	clip: Rectangle,
+	transform: Option<&Transform>,
+	rectangle: Rectangle,
+) -> Coverage {
+	if clip.width <= 0.0 || clip.height <= 0.0 {
+		return Coverage::Outside;
+	}
+	let corners = corners(rectangle);
+	let corners = match transform {
+		Some(transform) => corners.map(|corner| transform.transform_point(corner)),
+		None => corners,
+	};
+	let left = corners
+		.iter()
+		.fold(f32::INFINITY, |left, corner| left.min(corner.x));
+	let right = corners
+		.iter()
+		.fold(f32::NEG_INFINITY, |right, corner| right.max(corner.x));
+	let top = corners
+		.iter()
+		.fold(f32::INFINITY, |top, corner| top.min(corner.y));
+	let bottom = corners
+		.iter()
+		.fold(f32::NEG_INFINITY, |bottom, corner| bottom.max(corner.y));
+	let clip_right = clip.x + clip.width;
+	let clip_bottom = clip.y + clip.height;
+	if left >= clip.x && right <= clip_right && top >= clip.y && bottom <= clip_bottom {
+		Coverage::Inside
+	} else if left >= clip_right || right <= clip.x || top >= clip_bottom || bottom <= clip.y {
+		Coverage::Outside
+	} else {
+		Coverage::Partial
+	}
+}
+
+/// Returns the corners of the clip rectangle in the coordinates shapes
+/// are drawn in (before the transform is applied), or `None` if nothing
+/// drawn with the transform can be inside the clip rectangle.
+///
+/// The corners always make a convex shape, but if the transform rotates
+/// or skews, it won't be lined up with the axes.
+pub(crate) fn local_clip(clip: Rectangle, transform: Option<&Transform>) -> Option<[Point; 4]> {
+	if clip.width <= 0.0 || clip.height <= 0.0 {
+		return None;
+	}
+	let corners = corners(clip);
+	match transform {
+		Some(transform) => {
+			let inverse = inverse(transform)?;
+			Some(corners.map(|corner| inverse.transform_point(corner)))
+		}
+		None => Some(corners),
+	}
+}
+
+/// Returns the clip rectangle in the coordinates shapes are drawn in,
+/// or `None` if the transform rotates or skews (in which case the clip
+/// rectangle can't be represented as a `Rectangle`).
+pub(crate) fn local_clip_rectangle(
+	clip: Rectangle,
+	transform: Option<&Transform>,
+) -> Option<Rectangle> {
+	if let Some(transform) = transform {
+		if transform.b != 0.0 || transform.c != 0.0 {
+			return None;
+		}
+	}
+	let corners = local_clip(clip, transform)?;
+	let left = corners[0].x.min(corners[2].x);
+	let right = corners[0].x.max(corners[2].x);
+	let top = corners[0].y.min(corners[2].y);
+	let bottom = corners[0].y.max(corners[2].y);
+	Some(Rectangle::new(left, top, right - left, bottom - top))
+}
+
+/// Cuts off the parts of an image that are outside of a clip rectangle,
+/// returning the new source and destination rectangles, or `None` if
+/// none of the image is inside the clip rectangle.
+pub(crate) fn crop_image(
+	source: Rectangle,
+	destination: Rectangle,
+	clip: Rectangle,
+) -> Option<(Rectangle, Rectangle)> {
+	if destination.width <= 0.0 || destination.height <= 0.0 {
+		return None;
+	}
+	let visible = destination.intersection(&clip);
+	if visible.width <= 0.0 || visible.height <= 0.0 {
+		return None;
+	}
+	let scale_x = source.width / destination.width;
+	let scale_y = source.height / destination.height;
+	let source = Rectangle::new(
+		source.x + (visible.x - destination.x) * scale_x,
+		source.y + (visible.y - destination.y) * scale_y,
+		visible.width * scale_x,
+		visible.height * scale_y,
+	);
+	Some((source, visible))
+}
+
+/// Cuts off the parts of a convex polygon that are outside of
+/// a convex clip shape (using the Sutherland-Hodgman algorithm).
+pub(crate) fn clip_polygon(points: &[Point], clip: &[Point; 4]) -> Vec<Point> {
+	// the clip shape can be wound either way once it's been transformed,
+	// which flips which side of each edge is the inside
+	let winding = if signed_area(clip) < 0.0 { -1.0 } else { 1.0 };
+	let mut output = points.to_vec();
+	for i in 0..clip.len() {
+		let edge_start = clip[i];
+		let edge_end = clip[(i + 1) % clip.len()];
+		let side = |point: Point| {
+			winding
+				* ((edge_end.x - edge_start.x) * (point.y - edge_start.y)
+					- (edge_end.y - edge_start.y) * (point.x - edge_start.x))
+		};
+		let input = std::mem::take(&mut output);
+		for (j, &current) in input.iter().enumerate() {
+			let previous = input[(j + input.len() - 1) % input.len()];
+			let current_side = side(current);
+			let previous_side = side(previous);
+			if (current_side >= 0.0) != (previous_side >= 0.0) {
+				let t = previous_side / (previous_side - current_side);
+				output.push(Point::new(
+					previous.x + (current.x - previous.x) * t,
+					previous.y + (current.y - previous.y) * t,
+				));
+			}
+			if current_side >= 0.0 {
+				output.push(current);
+			}
+		}
+		if output.is_empty() {
+			break;
+		}
+	}
+	output
+}
+
+fn corners(rectangle: Rectangle) -> [Point; 4] {
+	[
+		Point::new(rectangle.x, rectangle.y),
+		Point::new(rectangle.x + rectangle.width, rectangle.y),
+		Point::new(
+			rectangle.x + rectangle.width,
+			rectangle.y + rectangle.height,
+		),
+		Point::new(rectangle.x, rectangle.y + rectangle.height),
+	]
+}
+
+fn inverse(transform: &Transform) -> Option<Transform> {
+	let determinant = transform.a * transform.d - transform.b * transform.c;
+	if determinant.abs() < f32::EPSILON {
+		return None;
+	}
+	Some(Transform {
+		a: transform.d / determinant,
+		b: -transform.b / determinant,
+		c: -transform.c / determinant,
+		d: transform.a / determinant,
+		e: (transform.c * transform.f - transform.d * transform.e) / determinant,
+		f: (transform.b * transform.e - transform.a * transform.f) / determinant,
+	})
+}
+
+fn signed_area(points: &[Point]) -> f32 {
+	let mut area = 0.0;
+	for (i, point) in points.iter().enumerate() {
+		let next = points[(i + 1) % points.len()];
+		area += point.x * next.y - next.x * point.y;
+	}
+	area / 2.0
+}
@@ -1,20 +1,22 @@
+mod clipping;
+mod tessellation;
+
+use clipping::Coverage;
 use ggez::{
-	graphics::{Font, MeshBuilder, Scale, Text},
+	event::{KeyCode, KeyMods},
+	graphics::{BlendMode, Drawable, Font, MeshBuilder, Scale, Text},
 	Context, GameResult,
 };
 use knobby::{
-	canvas::{ArcKind, Canvas, Color, DrawMode, DrawOperation, Style, TextStyle},
-	geometry::{Point, Rectangle},
-	gui::Gui,
-	input::MouseButton,
+	canvas::{
+		ArcKind, Color, CornerRadii, DrawMode, DrawOperation, ImageFilter, ImageStyle, Paint,
+		Style, TextStyle,
+	},
+	geometry::{Point, Rectangle, Transform},
+	gui::{Gui, ImageId, Resources},
+	input::{Key, Modifier, Modifiers, MouseButton, ScrollUnit},
 };
-
-fn convert_draw_mode(mode: DrawMode) -> ggez::graphics::DrawMode {
-	match mode {
-		DrawMode::Fill => ggez::graphics::DrawMode::fill(),
-		DrawMode::Stroke(width) => ggez::graphics::DrawMode::stroke(width),
-	}
-}
+use std::collections::{hash_map::Entry, HashMap};
 
 fn convert_color(color: Color) -> ggez::graphics::Color {
 	ggez::graphics::Color::new(color.red, color.green, color.blue, color.alpha)
@@ -24,6 +26,15 @@ fn convert_rectangle(rectangle: Rectangle) -> ggez::graphics::Rect {
 	ggez::graphics::Rect::new(rectangle.x, rectangle.y, rectangle.width, rectangle.height)
 }
 
+fn convert_transform(transform: Transform) -> ggez::mint::ColumnMatrix4<f32> {
+	ggez::mint::ColumnMatrix4::from([
+		[transform.a, transform.b, 0.0, 0.0],
+		[transform.c, transform.d, 0.0, 0.0],
+		[0.0, 0.0, 1.0, 0.0],
+		[transform.e, transform.f, 0.0, 1.0],
+	])
+}
+
 fn convert_point(point: Point) -> ggez::mint::Point2<f32> {
 	ggez::mint::Point2 {
 		x: point.x,
@@ -31,9 +42,147 @@ fn convert_point(point: Point) -> ggez::mint::Point2<f32> {
 	}
 }
 
+fn draw_laid_out_text(
+	ctx: &mut Context,
+	text: &Text,
+	bounds: Rectangle,
+	color: Color,
+) -> GameResult {
+	ggez::graphics::draw(
+		ctx,
+		text,
+		ggez::graphics::DrawParam::new()
+			.dest(convert_point(Point::new(bounds.x, bounds.y)))
+			.color(convert_color(color)),
+	)
+}
+
+/// Draws the part of the clip layer inside the clip rectangle
+/// to the screen.
+fn draw_clip_layer(
+	ctx: &mut Context,
+	layer: &ggez::graphics::Canvas,
+	rectangle: Rectangle,
+) -> GameResult {
+	let screen = ggez::graphics::screen_coordinates(ctx);
+	// the clip rectangle isn't affected by transforms
+	ggez::graphics::push_transform(ctx, Some(convert_transform(Transform::identity())));
+	ggez::graphics::apply_transformations(ctx)?;
+	ggez::graphics::draw(
+		ctx,
+		layer,
+		ggez::graphics::DrawParam::new()
+			.src(ggez::graphics::Rect::new(
+				(rectangle.x - screen.x) / screen.w,
+				(rectangle.y - screen.y) / screen.h,
+				rectangle.width / screen.w,
+				rectangle.height / screen.h,
+			))
+			.dest(convert_point(Point::new(rectangle.x, rectangle.y)))
+			.scale([
+				screen.w / layer.image().width() as f32,
+				screen.h / layer.image().height() as f32,
+			]),
+	)?;
+	ggez::graphics::pop_transform(ctx);
+	ggez::graphics::apply_transformations(ctx)
+}
+
+fn convert_key_code(key_code: KeyCode) -> Option<Key> {
+	let key = match key_code {
+		KeyCode::Tab => Key::Tab,
+		KeyCode::Return | KeyCode::NumpadEnter => Key::Enter,
+		KeyCode::Escape => Key::Escape,
+		KeyCode::Back => Key::Backspace,
+		KeyCode::Delete => Key::Delete,
+		KeyCode::Space => Key::Space,
+		KeyCode::Left => Key::Left,
+		KeyCode::Right => Key::Right,
+		KeyCode::Up => Key::Up,
+		KeyCode::Down => Key::Down,
+		KeyCode::Home => Key::Home,
+		KeyCode::End => Key::End,
+		KeyCode::PageUp => Key::PageUp,
+		KeyCode::PageDown => Key::PageDown,
+		KeyCode::A => Key::Character('a'),
+		KeyCode::B => Key::Character('b'),
+		KeyCode::C => Key::Character('c'),
+		KeyCode::D => Key::Character('d'),
+		KeyCode::E => Key::Character('e'),
+		KeyCode::F => Key::Character('f'),
+		KeyCode::G => Key::Character('g'),
+		KeyCode::H => Key::Character('h'),
+		KeyCode::I => Key::Character('i'),
+		KeyCode::J => Key::Character('j'),
+		KeyCode::K => Key::Character('k'),
+		KeyCode::L => Key::Character('l'),
+		KeyCode::M => Key::Character('m'),
+		KeyCode::N => Key::Character('n'),
+		KeyCode::O => Key::Character('o'),
+		KeyCode::P => Key::Character('p'),
+		KeyCode::Q => Key::Character('q'),
+		KeyCode::R => Key::Character('r'),
+		KeyCode::S => Key::Character('s'),
+		KeyCode::T => Key::Character('t'),
+		KeyCode::U => Key::Character('u'),
+		KeyCode::V => Key::Character('v'),
+		KeyCode::W => Key::Character('w'),
+		KeyCode::X => Key::Character('x'),
+		KeyCode::Y => Key::Character('y'),
+		KeyCode::Z => Key::Character('z'),
+		KeyCode::Key0 | KeyCode::Numpad0 => Key::Character('0'),
+		KeyCode::Key1 | KeyCode::Numpad1 => Key::Character('1'),
+		KeyCode::Key2 | KeyCode::Numpad2 => Key::Character('2'),
+		KeyCode::Key3 | KeyCode::Numpad3 => Key::Character('3'),
+		KeyCode::Key4 | KeyCode::Numpad4 => Key::Character('4'),
+		KeyCode::Key5 | KeyCode::Numpad5 => Key::Character('5'),
+		KeyCode::Key6 | KeyCode::Numpad6 => Key::Character('6'),
+		KeyCode::Key7 | KeyCode::Numpad7 => Key::Character('7'),
+		KeyCode::Key8 | KeyCode::Numpad8 => Key::Character('8'),
+		KeyCode::Key9 | KeyCode::Numpad9 => Key::Character('9'),
+		KeyCode::Minus | KeyCode::Subtract => Key::Character('-'),
+		KeyCode::Equals => Key::Character('='),
+		KeyCode::Add => Key::Character('+'),
+		KeyCode::Period | KeyCode::Decimal => Key::Character('.'),
+		KeyCode::Comma => Key::Character(','),
+		KeyCode::Slash | KeyCode::Divide => Key::Character('/'),
+		_ => return None,
+	};
+	Some(key)
+}
+
+fn convert_key_mods(key_mods: KeyMods) -> Modifiers {
+	let mut modifiers = Modifiers::new();
+	if key_mods.contains(KeyMods::SHIFT) {
+		modifiers.insert(Modifier::Shift);
+	}
+	if key_mods.contains(KeyMods::CTRL) {
+		modifiers.insert(Modifier::Control);
+	}
+	if key_mods.contains(KeyMods::ALT) {
+		modifiers.insert(Modifier::Alt);
+	}
+	if key_mods.contains(KeyMods::LOGO) {
+		modifiers.insert(Modifier::Command);
+	}
+	modifiers
+}
+
 pub struct GgezBackend<CustomEvent> {
 	pub gui: Gui<CustomEvent>,
+	renderer: Renderer,
+}
+
+/// The ggez resources used to draw the GUI, kept apart from the GUI
+/// so they can be used while the GUI's draw operations are borrowed.
+struct Renderer {
 	fonts: Vec<Font>,
+	// ggez versions of the images in the GUI's resources,
+	// created the first time each image is drawn
+	images: HashMap<ImageId, ggez::graphics::Image>,
+	// an offscreen canvas for drawing things that can't be clipped
+	// by hand, like text that's partly outside of the clip rectangle
+	clip_layer: Option<ggez::graphics::Canvas>,
 }
 
 impl<CustomEvent> GgezBackend<CustomEvent>
@@ -43,30 +192,43 @@ where
 	pub fn new() -> Self {
 		Self {
 			gui: Gui::new(),
-			fonts: vec![],
+			renderer: Renderer {
+				fonts: vec![],
+				images: HashMap::new(),
+				clip_layer: None,
+			},
 		}
 	}
 
 	pub fn load_font(&mut self, ctx: &mut Context, font_data: &'static [u8]) -> GameResult {
-		self.fonts.push(Font::new_glyph_font_bytes(ctx, font_data)?);
+		self.renderer
+			.fonts
+			.push(Font::new_glyph_font_bytes(ctx, font_data)?);
 		self.gui.resources.load_font(font_data).unwrap();
 		Ok(())
 	}
 
+	pub fn update(&mut self, ctx: &mut Context) -> GameResult {
+		self.gui.sync_audio();
+		self.gui.update(ggez::timer::delta(ctx).as_secs_f32());
+		Ok(())
+	}
+
 	pub fn mouse_motion_event(
 		&mut self,
-		_ctx: &mut ggez::Context,
+		ctx: &mut ggez::Context,
 		x: f32,
 		y: f32,
 		dx: f32,
 		dy: f32,
 	) {
-		self.gui.on_mouse_move(x, y, dx, dy);
+		let modifiers = convert_key_mods(ggez::input::keyboard::active_mods(ctx));
+		self.gui.on_mouse_move(x, y, dx, dy, modifiers);
 	}
 
 	pub fn mouse_button_down_event(
 		&mut self,
-		_ctx: &mut ggez::Context,
+		ctx: &mut ggez::Context,
 		button: ggez::event::MouseButton,
 		x: f32,
 		y: f32,
@@ -79,12 +241,14 @@ where
 				return;
 			}
 		};
-		self.gui.on_mouse_down(button, x, y);
+		let modifiers = convert_key_mods(ggez::input::keyboard::active_mods(ctx));
+		let time = ggez::timer::time_since_start(ctx).as_secs_f64();
+		self.gui.on_mouse_down(button, x, y, modifiers, time);
 	}
 
 	pub fn mouse_button_up_event(
 		&mut self,
-		_ctx: &mut ggez::Context,
+		ctx: &mut ggez::Context,
 		button: ggez::event::MouseButton,
 		x: f32,
 		y: f32,
@@ -97,35 +261,123 @@ where
 				return;
 			}
 		};
-		self.gui.on_mouse_up(button, x, y);
+		let modifiers = convert_key_mods(ggez::input::keyboard::active_mods(ctx));
+		self.gui.on_mouse_up(button, x, y, modifiers);
 	}
 
+	pub fn mouse_wheel_event(&mut self, ctx: &mut ggez::Context, x: f32, y: f32) {
+		let position = ggez::input::mouse::position(ctx);
+		let modifiers = convert_key_mods(ggez::input::keyboard::active_mods(ctx));
+		// ggez doesn't tell us whether the scroll came from a mouse wheel
+		// or a trackpad, so we assume it's a mouse wheel
+		self.gui
+			.on_scroll(position.x, position.y, x, y, ScrollUnit::Lines, modifiers);
+	}
+
+	pub fn resize_event(&mut self, _ctx: &mut ggez::Context, _width: f32, _height: f32) {
+		// the clip layer is the size of the window, so it'll
+		// need to be recreated
+		self.renderer.clip_layer = None;
+		self.gui.request_full_redraw();
+	}
+
+	pub fn focus_event(&mut self, _ctx: &mut ggez::Context, gained: bool) {
+		if !gained {
+			self.gui.cancel_mouse_input();
+		}
+	}
+
+	pub fn key_down_event(
+		&mut self,
+		_ctx: &mut ggez::Context,
+		key_code: KeyCode,
+		key_mods: KeyMods,
+		_repeat: bool,
+	) {
+		if let Some(key) = convert_key_code(key_code) {
+			self.gui.on_key_down(key, convert_key_mods(key_mods));
+		}
+	}
+
+	pub fn key_up_event(&mut self, _ctx: &mut ggez::Context, key_code: KeyCode, key_mods: KeyMods) {
+		if let Some(key) = convert_key_code(key_code) {
+			self.gui.on_key_up(key, convert_key_mods(key_mods));
+		}
+	}
+
+	pub fn text_input_event(&mut self, _ctx: &mut ggez::Context, character: char) {
+		// control characters (backspace, tab, etc.) are sent as key events instead
+		if !character.is_control() {
+			self.gui.on_text_input(character);
+		}
+	}
+
+	pub fn draw_text(
+		&mut self,
+		ctx: &mut Context,
+		text: &str,
+		position: Point,
+		style: &TextStyle,
+	) -> GameResult {
+		self.renderer.draw_text(ctx, text, position, style)
+	}
+
+	/// Draws the GUI, borrowing its cached draw operations
+	/// rather than copying them.
+	pub fn draw(&mut self, ctx: &mut Context) -> GameResult {
+		self.gui.update_draw_cache();
+		self.renderer
+			.draw(ctx, &self.gui.resources, self.gui.draw_operations())
+	}
+}
+
+impl Renderer {
 	fn draw_rectangle(
 		&mut self,
 		mesh_builder: &mut MeshBuilder,
 		rectangle: Rectangle,
-		style: Style,
+		color: Color,
 	) {
 		mesh_builder.rectangle(
-			convert_draw_mode(style.mode),
+			ggez::graphics::DrawMode::fill(),
 			convert_rectangle(rectangle),
-			convert_color(style.color),
+			convert_color(color),
 		);
 	}
 
+	fn draw_rounded_rectangle(
+		&mut self,
+		mesh_builder: &mut MeshBuilder,
+		rectangle: Rectangle,
+		radii: CornerRadii,
+		color: Color,
+	) -> GameResult {
+		let points: Vec<ggez::mint::Point2<f32>> =
+			tessellation::rounded_rectangle_points(rectangle, radii)
+				.iter()
+				.map(|point| convert_point(*point))
+				.collect();
+		mesh_builder.polygon(
+			ggez::graphics::DrawMode::fill(),
+			&points,
+			convert_color(color),
+		)?;
+		Ok(())
+	}
+
 	fn draw_circle(
 		&mut self,
 		mesh_builder: &mut MeshBuilder,
 		position: Point,
 		radius: f32,
-		style: Style,
+		color: Color,
 	) {
 		mesh_builder.circle(
-			convert_draw_mode(style.mode),
+			ggez::graphics::DrawMode::fill(),
 			convert_point(position),
 			radius,
 			0.1,
-			convert_color(style.color),
+			convert_color(color),
 		);
 	}
 
@@ -137,121 +389,396 @@ where
 		radius: f32,
 		angle1: f32,
 		angle2: f32,
-		style: Style,
+		color: Color,
 	) -> GameResult {
-		let mut points = vec![];
-		let segments = radius.ceil() as usize;
-		for i in 0..=segments {
-			let angle = angle1 + (angle2 - angle1) * (i as f32 / segments as f32);
-			points.push(Point::new(
-				position.x + radius * angle.cos(),
-				position.y + radius * angle.sin(),
-			));
-		}
-		match kind {
-			ArcKind::Pie => {
-				points.push(position);
-				points.push(Point::new(
-					position.x + radius * angle1.cos(),
-					position.y + radius * angle1.sin(),
-				));
-			}
-			ArcKind::Open => {}
-			ArcKind::Closed => {
-				points.push(Point::new(
-					position.x + radius * angle1.cos(),
-					position.y + radius * angle1.sin(),
-				));
-			}
+		let points: Vec<ggez::mint::Point2<f32>> =
+			tessellation::arc_points(kind, position, radius, angle1, angle2)
+				.iter()
+				.map(|point| convert_point(*point))
+				.collect();
+		mesh_builder.polygon(
+			ggez::graphics::DrawMode::fill(),
+			&points,
+			convert_color(color),
+		)?;
+		Ok(())
+	}
+
+	/// Returns the ggez text for a piece of text along with the
+	/// rectangle it takes up once it's aligned, or `None` if the
+	/// font hasn't been loaded.
+	fn layout_text(
+		&self,
+		ctx: &mut Context,
+		text: &str,
+		position: Point,
+		style: &TextStyle,
+	) -> Option<(Text, Rectangle)> {
+		let font = self.fonts.get(style.font_id)?;
+		let mut t = Text::new(text);
+		t.set_font(
+			*font,
+			Scale {
+				x: style.size,
+				y: style.size,
+			},
+		);
+		let width = t.width(ctx) as f32;
+		let height = t.height(ctx) as f32;
+		let bounds = Rectangle::new(
+			position.x - width * style.horizontal_alignment.as_f32(),
+			position.y - height * style.vertical_alignment.as_f32(),
+			width,
+			height,
+		);
+		Some((t, bounds))
+	}
+
+	fn draw_text(
+		&mut self,
+		ctx: &mut Context,
+		text: &str,
+		position: Point,
+		style: &TextStyle,
+	) -> GameResult {
+		match self.layout_text(ctx, text, position, style) {
+			Some((t, bounds)) => draw_laid_out_text(ctx, &t, bounds, style.color),
+			None => Ok(()),
 		}
-		let mint_points: Vec<ggez::mint::Point2<f32>> =
-			points.iter().map(|point| convert_point(*point)).collect();
-		match style.mode {
-			DrawMode::Fill => {
-				mesh_builder.polygon(
-					convert_draw_mode(style.mode),
-					&mint_points,
-					convert_color(style.color),
-				)?;
-			}
-			DrawMode::Stroke(width) => {
-				mesh_builder.line(&mint_points, width, convert_color(style.color))?;
-			}
+	}
+
+	fn draw_image(
+		&mut self,
+		ctx: &mut Context,
+		resources: &Resources,
+		image_id: ImageId,
+		source: Option<Rectangle>,
+		destination: Rectangle,
+		style: ImageStyle,
+	) -> GameResult {
+		let image = match self.images.entry(image_id) {
+			Entry::Occupied(entry) => entry.into_mut(),
+			Entry::Vacant(entry) => match resources.get_image(image_id) {
+				// images are never bigger than `MAX_IMAGE_SIZE`,
+				// so their sizes always fit in a u16
+				Some(image) => entry.insert(ggez::graphics::Image::from_rgba8(
+					ctx,
+					image.width as u16,
+					image.height as u16,
+					image.pixels(),
+				)?),
+				None => return Ok(()),
+			},
+		};
+		image.set_filter(match style.filter {
+			ImageFilter::Linear => ggez::graphics::FilterMode::Linear,
+			ImageFilter::Nearest => ggez::graphics::FilterMode::Nearest,
+		});
+		let width = image.width() as f32;
+		let height = image.height() as f32;
+		let source = source.unwrap_or_else(|| Rectangle::new(0.0, 0.0, width, height));
+		if source.width <= 0.0 || source.height <= 0.0 {
+			return Ok(());
 		}
+		// ggez expects the source rectangle to be from 0-1
+		// rather than in pixels
+		ggez::graphics::draw(
+			ctx,
+			image,
+			ggez::graphics::DrawParam::new()
+				.src(ggez::graphics::Rect::new(
+					source.x / width,
+					source.y / height,
+					source.width / width,
+					source.height / height,
+				))
+				.dest(convert_point(Point::new(destination.x, destination.y)))
+				.scale([
+					destination.width / source.width,
+					destination.height / source.height,
+				])
+				.color(convert_color(style.tint)),
+		)?;
 		Ok(())
 	}
 
-	pub fn draw_text(
+	/// Draws the shapes that have been added to the mesh builder
+	/// so far and starts a new mesh.
+	fn flush_meshes(
 		&mut self,
 		ctx: &mut Context,
-		text: String,
-		mut position: Point,
-		style: TextStyle,
+		mesh_builder: &mut MeshBuilder,
+		created_meshes: &mut bool,
 	) -> GameResult {
-		if let Some(font) = self.fonts.get(style.font_id) {
-			let mut t = Text::new(text);
-			t.set_font(
-				*font,
-				Scale {
-					x: style.size,
-					y: style.size,
-				},
-			);
-			position.x -= t.width(ctx) as f32 * style.horizontal_alignment.as_f32();
-			position.y -= t.height(ctx) as f32 * style.vertical_alignment.as_f32();
-			ggez::graphics::draw(
-				ctx,
-				&t,
-				ggez::graphics::DrawParam::new()
-					.dest(convert_point(position))
-					.color(convert_color(style.color)),
-			)?;
+		if *created_meshes {
+			let mesh = mesh_builder.build(ctx)?;
+			ggez::graphics::draw(ctx, &mesh, ggez::graphics::DrawParam::new())?;
+			*mesh_builder = MeshBuilder::new();
+			*created_meshes = false;
 		}
 		Ok(())
 	}
 
-	pub fn draw(&mut self, ctx: &mut Context) -> GameResult {
-		let mut canvas = Canvas::new();
-		self.gui.draw(&mut canvas);
-		if !canvas.operations.len() == 0 {
-			return Ok(());
-		}
+	/// Draws something to the offscreen clip layer and then draws
+	/// the part of the layer inside the clip rectangle to the screen.
+	///
+	/// This is slow, so it's only used for things that can't be
+	/// clipped by hand.
+	fn draw_in_clip_layer(
+		&mut self,
+		ctx: &mut Context,
+		rectangle: Rectangle,
+		draw: impl FnOnce(&mut Self, &mut Context) -> GameResult,
+	) -> GameResult {
+		let layer = match self.clip_layer.take() {
+			Some(layer) => layer,
+			None => {
+				let mut layer = ggez::graphics::Canvas::with_window_size(ctx)?;
+				// the colors in the layer have already been multiplied by
+				// their alpha, so blending them with the alpha again would
+				// make anything translucent too faint
+				layer.set_blend_mode(Some(BlendMode::Premultiplied));
+				layer
+			}
+		};
+		ggez::graphics::set_canvas(ctx, Some(&layer));
+		ggez::graphics::clear(ctx, ggez::graphics::Color::new(0.0, 0.0, 0.0, 0.0));
+		let drawn = draw(self, ctx);
+		ggez::graphics::set_canvas(ctx, None);
+		let result = drawn.and_then(|()| draw_clip_layer(ctx, &layer, rectangle));
+		self.clip_layer = Some(layer);
+		result
+	}
+
+	/// Draws the operations in order.
+	fn draw<'a>(
+		&mut self,
+		ctx: &mut Context,
+		resources: &Resources,
+		operations: impl Iterator<Item = &'a DrawOperation>,
+	) -> GameResult {
 		let mut mesh_builder = MeshBuilder::new();
 		let mut created_meshes = false;
-		for operation in canvas.operations {
+		// ggez replaces the current transform rather than combining
+		// transforms, so we keep track of the combined transforms ourselves
+		let mut transforms: Vec<Transform> = vec![];
+		let mut clips: Vec<Rectangle> = vec![];
+		for operation in operations {
 			match operation {
-				DrawOperation::Rectangle(rectangle, style) => {
-					self.draw_rectangle(&mut mesh_builder, rectangle, style);
+				DrawOperation::Rectangle(
+					rectangle,
+					Style {
+						mode: DrawMode::Fill,
+						paint: Paint::Solid(color),
+					},
+				) if clips.is_empty() => {
+					self.draw_rectangle(&mut mesh_builder, *rectangle, *color);
 					created_meshes = true;
 				}
-				DrawOperation::Circle(position, radius, style) => {
-					self.draw_circle(&mut mesh_builder, position, radius, style);
+				DrawOperation::RoundedRectangle(
+					rectangle,
+					radii,
+					Style {
+						mode: DrawMode::Fill,
+						paint: Paint::Solid(color),
+					},
+				) if clips.is_empty() => {
+					self.draw_rounded_rectangle(&mut mesh_builder, *rectangle, *radii, *color)?;
 					created_meshes = true;
 				}
-				DrawOperation::Arc(kind, position, radius, angle1, angle2, style) => {
+				DrawOperation::Circle(
+					position,
+					radius,
+					Style {
+						mode: DrawMode::Fill,
+						paint: Paint::Solid(color),
+					},
+				) if clips.is_empty() => {
+					self.draw_circle(&mut mesh_builder, *position, *radius, *color);
+					created_meshes = true;
+				}
+				DrawOperation::Arc(
+					kind,
+					position,
+					radius,
+					angle1,
+					angle2,
+					Style {
+						mode: DrawMode::Fill,
+						paint: Paint::Solid(color),
+					},
+				) if clips.is_empty() => {
 					self.draw_arc(
 						&mut mesh_builder,
-						kind,
-						position,
-						radius,
-						angle1,
-						angle2,
-						style,
+						*kind,
+						*position,
+						*radius,
+						*angle1,
+						*angle2,
+						*color,
 					)?;
 					created_meshes = true;
 				}
-				DrawOperation::Polyline(_, _) => todo!(),
-				DrawOperation::Polygon(_, _) => todo!(),
 				DrawOperation::Text(text, position, style) => {
-					self.draw_text(ctx, text, position, style)?;
+					let (t, bounds) = match self.layout_text(ctx, text, *position, style) {
+						Some(laid_out) => laid_out,
+						None => continue,
+					};
+					// text is drawn right away, so any shapes drawn
+					// before it need to be drawn first
+					self.flush_meshes(ctx, &mut mesh_builder, &mut created_meshes)?;
+					let transform = transforms.last();
+					match clips
+						.last()
+						.map(|clip| (*clip, clipping::coverage(*clip, transform, bounds)))
+					{
+						None | Some((_, Coverage::Inside)) => {
+							draw_laid_out_text(ctx, &t, bounds, style.color)?;
+						}
+						Some((_, Coverage::Outside)) => {}
+						// text can't be cut up by hand, so it has to be drawn
+						// to the clip layer
+						Some((clip, Coverage::Partial)) => {
+							self.draw_in_clip_layer(ctx, clip, |_, ctx| {
+								draw_laid_out_text(ctx, &t, bounds, style.color)
+							})?;
+						}
+					}
+				}
+				DrawOperation::Image(image_id, source, destination, style) => {
+					// images are drawn right away, so any shapes drawn
+					// before them need to be drawn first
+					self.flush_meshes(ctx, &mut mesh_builder, &mut created_meshes)?;
+					let transform = transforms.last();
+					match clips
+						.last()
+						.map(|clip| (*clip, clipping::coverage(*clip, transform, *destination)))
+					{
+						None | Some((_, Coverage::Inside)) => {
+							self.draw_image(
+								ctx,
+								resources,
+								*image_id,
+								*source,
+								*destination,
+								*style,
+							)?;
+						}
+						Some((_, Coverage::Outside)) => {}
+						Some((clip, Coverage::Partial)) => {
+							match clipping::local_clip_rectangle(clip, transform) {
+								// if the transform doesn't rotate or skew the image,
+								// the parts outside the clip rectangle can be cut off
+								Some(local_clip) => {
+									let source = source.or_else(|| {
+										resources.get_image(*image_id).map(|image| {
+											Rectangle::new(
+												0.0,
+												0.0,
+												image.width as f32,
+												image.height as f32,
+											)
+										})
+									});
+									let cropped = source.and_then(|source| {
+										clipping::crop_image(source, *destination, local_clip)
+									});
+									if let Some((source, destination)) = cropped {
+										self.draw_image(
+											ctx,
+											resources,
+											*image_id,
+											Some(source),
+											destination,
+											*style,
+										)?;
+									}
+								}
+								None => {
+									self.draw_in_clip_layer(ctx, clip, |renderer, ctx| {
+										renderer.draw_image(
+											ctx,
+											resources,
+											*image_id,
+											*source,
+											*destination,
+											*style,
+										)
+									})?;
+								}
+							}
+						}
+					}
+				}
+				DrawOperation::PushTransform(transform) => {
+					// shapes drawn before the transform was pushed
+					// shouldn't be affected by it
+					self.flush_meshes(ctx, &mut mesh_builder, &mut created_meshes)?;
+					let combined = match transforms.last() {
+						Some(current) => transform.then(*current),
+						None => *transform,
+					};
+					transforms.push(combined);
+					ggez::graphics::push_transform(ctx, Some(convert_transform(combined)));
+					ggez::graphics::apply_transformations(ctx)?;
+				}
+				DrawOperation::PopTransform => {
+					if transforms.pop().is_some() {
+						self.flush_meshes(ctx, &mut mesh_builder, &mut created_meshes)?;
+						ggez::graphics::pop_transform(ctx);
+						ggez::graphics::apply_transformations(ctx)?;
+					}
+				}
+				// shapes and images are cut to the clip rectangle as they're
+				// added, so changing the clip rectangle doesn't need to
+				// draw anything
+				DrawOperation::PushClip(rectangle) => {
+					let clipped = match clips.last() {
+						Some(current) => rectangle.intersection(current),
+						None => *rectangle,
+					};
+					clips.push(clipped);
+				}
+				DrawOperation::PopClip => {
+					clips.pop();
+				}
+				// ggez can't color each vertex of its built-in shapes differently,
+				// draw strokes with caps, joins, and dashes, or clip shapes,
+				// so everything else is tessellated by hand
+				operation => {
+					let transform = transforms.last();
+					let local_clip = match clips.last() {
+						Some(clip) => match clipping::local_clip(*clip, transform) {
+							Some(local_clip) => Some(local_clip),
+							// nothing drawn now would be visible
+							None => continue,
+						},
+						None => None,
+					};
+					let tolerance = tessellation::path_tolerance(transform);
+					if let Some((outlines, fill_rule, style)) =
+						tessellation::shape_outlines(operation, tolerance)
+					{
+						tessellation::add_shape(
+							&mut mesh_builder,
+							&outlines,
+							fill_rule,
+							style,
+							local_clip.as_ref(),
+						);
+						created_meshes = true;
+					}
 				}
 			}
 		}
-		if !created_meshes {
-			return Ok(());
+		self.flush_meshes(ctx, &mut mesh_builder, &mut created_meshes)?;
+		// make sure transforms don't carry over to the next frame
+		if !transforms.is_empty() {
+			for _ in transforms {
+				ggez::graphics::pop_transform(ctx);
+			}
+			ggez::graphics::apply_transformations(ctx)?;
 		}
-		let mesh = mesh_builder.build(ctx)?;
-		ggez::graphics::draw(ctx, &mesh, ggez::graphics::DrawParam::new())?;
 		Ok(())
 	}
 }
@@ -0,0 +1,305 @@
+use crate::clipping::clip_polygon;
+use ggez::graphics::{MeshBuilder, Vertex};
+use knobby::{
+	canvas::{ArcKind, CornerRadii, DrawMode, DrawOperation, Paint, Style},
+	fill::fill_triangles,
+	geometry::{Point, Rectangle, Transform},
+	path::{FillRule, Subpath},
+	stroker::stroke_triangles,
+};
+use std::f32::consts::PI;
+
+/// The maximum distance (in pixels) between a curve in a path
+/// and the lines used to draw it.
+const PATH_TOLERANCE: f32 = 0.1;
+
+/// The longest a triangle edge can be before it's split up, so the
+/// colors of gradients (which are only calculated at each vertex)
+/// blend smoothly.
+const MAX_GRADIENT_EDGE_LENGTH: f32 = 8.0;
+
+/// The most pieces each edge of a triangle will be split into
+/// when drawing a gradient.
+const MAX_GRADIENT_DIVISIONS: usize = 64;
+
+/// Returns the tolerance to flatten paths with so that curves stay within
+/// `PATH_TOLERANCE` pixels of their lines once the transform is applied.
+pub(crate) fn path_tolerance(transform: Option<&Transform>) -> f32 {
+	let transform = match transform {
+		Some(transform) => transform,
+		None => return PATH_TOLERANCE,
+	};
+	// the most the transform can stretch a distance by is the largest
+	// singular value of its 2x2 matrix
+	let sum = transform.a * transform.a
+		+ transform.b * transform.b
+		+ transform.c * transform.c
+		+ transform.d * transform.d;
+	let determinant = transform.a * transform.d - transform.b * transform.c;
+	let discriminant = (sum * sum - 4.0 * determinant * determinant).max(0.0);
+	let scale = ((sum + discriminant.sqrt()) / 2.0).sqrt();
+	if scale > 0.0001 {
+		PATH_TOLERANCE / scale
+	} else {
+		PATH_TOLERANCE
+	}
+}
+
+/// Returns the outline of a rectangle with rounded corners,
+/// going clockwise from the top-left corner.
+pub(crate) fn rounded_rectangle_points(rectangle: Rectangle, radii: CornerRadii) -> Vec<Point> {
+	// scale the radii down so adjacent corners don't overlap
+	let mut scale: f32 = 1.0;
+	for (first, second, length) in &[
+		(radii.top_left, radii.top_right, rectangle.width),
+		(radii.bottom_left, radii.bottom_right, rectangle.width),
+		(radii.top_left, radii.bottom_left, rectangle.height),
+		(radii.top_right, radii.bottom_right, rectangle.height),
+	] {
+		let sum = first.max(0.0) + second.max(0.0);
+		if sum > *length {
+			scale = scale.min(length / sum);
+		}
+	}
+	let left = rectangle.x;
+	let top = rectangle.y;
+	let right = rectangle.x + rectangle.width;
+	let bottom = rectangle.y + rectangle.height;
+	// the direction values point from the corner of the rectangle
+	// towards the center of the corner's arc
+	let mut points: Vec<Point> = vec![];
+	for (radius, corner_x, corner_y, direction_x, direction_y, start_angle) in &[
+		(radii.top_left, left, top, 1.0, 1.0, PI),
+		(radii.top_right, right, top, -1.0, 1.0, PI * 1.5),
+		(radii.bottom_right, right, bottom, -1.0, -1.0, 0.0),
+		(radii.bottom_left, left, bottom, 1.0, -1.0, PI * 0.5),
+	] {
+		let radius = radius.max(0.0) * scale;
+		let center = Point::new(
+			corner_x + direction_x * radius,
+			corner_y + direction_y * radius,
+		);
+		let segments = (radius.ceil() as usize).max(1);
+		for i in 0..=segments {
+			let angle = start_angle + PI * 0.5 * (i as f32 / segments as f32);
+			let point = Point::new(
+				center.x + radius * angle.cos(),
+				center.y + radius * angle.sin(),
+			);
+			// skip points that are in the same place as the
+			// previous one, like the corners of a pill shape
+			if let Some(previous) = points.last() {
+				if (previous.x - point.x).abs() < 0.001 && (previous.y - point.y).abs() < 0.001 {
+					continue;
+				}
+			}
+			points.push(point);
+		}
+	}
+	points
+}
+
+/// Returns the outline of an arc.
+pub(crate) fn arc_points(
+	kind: ArcKind,
+	position: Point,
+	radius: f32,
+	angle1: f32,
+	angle2: f32,
+) -> Vec<Point> {
+	let mut points = vec![];
+	let segments = (radius.ceil() as usize).max(1);
+	for i in 0..=segments {
+		let angle = angle1 + (angle2 - angle1) * (i as f32 / segments as f32);
+		points.push(Point::new(
+			position.x + radius * angle.cos(),
+			position.y + radius * angle.sin(),
+		));
+	}
+	match kind {
+		ArcKind::Pie => {
+			points.push(position);
+			points.push(Point::new(
+				position.x + radius * angle1.cos(),
+				position.y + radius * angle1.sin(),
+			));
+		}
+		ArcKind::Open => {}
+		ArcKind::Closed => {
+			points.push(Point::new(
+				position.x + radius * angle1.cos(),
+				position.y + radius * angle1.sin(),
+			));
+		}
+	}
+	points
+}
+
+/// Returns the outlines of a shape along with its fill rule and style,
+/// or `None` if the draw operation isn't a shape.
+///
+/// Paths are flattened with the given tolerance (see `path_tolerance`).
+pub(crate) fn shape_outlines(
+	operation: &DrawOperation,
+	tolerance: f32,
+) -> Option<(Vec<Subpath>, FillRule, &Style)> {
+	let closed = |points: Vec<Point>| Subpath {
+		points,
+		closed: true,
+	};
+	match operation {
+		DrawOperation::Rectangle(rectangle, style) => {
+			let points = vec![
+				Point::new(rectangle.x, rectangle.y),
+				Point::new(rectangle.x + rectangle.width, rectangle.y),
+				Point::new(
+					rectangle.x + rectangle.width,
+					rectangle.y + rectangle.height,
+				),
+				Point::new(rectangle.x, rectangle.y + rectangle.height),
+			];
+			Some((vec![closed(points)], FillRule::NonZero, style))
+		}
+		DrawOperation::RoundedRectangle(rectangle, radii, style) => Some((
+			vec![closed(rounded_rectangle_points(*rectangle, *radii))],
+			FillRule::NonZero,
+			style,
+		)),
+		DrawOperation::Circle(position, radius, style) => {
+			let mut points = arc_points(ArcKind::Open, *position, *radius, 0.0, 2.0 * PI);
+			// the last point is the same as the first one
+			points.pop();
+			Some((vec![closed(points)], FillRule::NonZero, style))
+		}
+		DrawOperation::Arc(kind, position, radius, angle1, angle2, style) => {
+			let points = arc_points(*kind, *position, *radius, *angle1, *angle2);
+			let subpath = Subpath {
+				points,
+				closed: !matches!(kind, ArcKind::Open),
+			};
+			Some((vec![subpath], FillRule::NonZero, style))
+		}
+		DrawOperation::Polyline(points, style) => Some((
+			vec![Subpath {
+				points: points.clone(),
+				closed: false,
+			}],
+			FillRule::NonZero,
+			style,
+		)),
+		DrawOperation::Polygon(points, style) => {
+			Some((vec![closed(points.clone())], FillRule::NonZero, style))
+		}
+		DrawOperation::Path(path, style) => {
+			Some((path.flatten(tolerance), path.fill_rule(), style))
+		}
+		_ => None,
+	}
+}
+
+/// Builds the triangles for a shape, giving each vertex its own
+/// color, and adds them to the mesh builder.
+///
+/// If a clip shape is given (see `clipping::local_clip`), the parts
+/// of the triangles outside of it are cut off.
+pub(crate) fn add_shape(
+	mesh_builder: &mut MeshBuilder,
+	outlines: &[Subpath],
+	fill_rule: FillRule,
+	style: &Style,
+	clip: Option<&[Point; 4]>,
+) {
+	let mut mesh = ShapeMesh {
+		paint: &style.paint,
+		vertices: vec![],
+		indices: vec![],
+	};
+	let triangles = match &style.mode {
+		DrawMode::Fill => fill_triangles(outlines, fill_rule),
+		DrawMode::Stroke(stroke) => outlines
+			.iter()
+			.flat_map(|outline| stroke_triangles(&outline.points, outline.closed, stroke))
+			.collect(),
+	};
+	for triangle in triangles {
+		match clip {
+			Some(clip) => {
+				let polygon = clip_polygon(&triangle, clip);
+				for i in 1..polygon.len().saturating_sub(1) {
+					mesh.add_triangle([polygon[0], polygon[i], polygon[i + 1]]);
+				}
+			}
+			None => mesh.add_triangle(triangle),
+		}
+	}
+	if !mesh.indices.is_empty() {
+		mesh_builder.raw(&mesh.vertices, &mesh.indices, None);
+	}
+}
+
+/// A list of triangles whose vertices are colored by a paint.
+struct ShapeMesh<'a> {
+	paint: &'a Paint,
+	vertices: Vec<Vertex>,
+	indices: Vec<u32>,
+}
+
+impl<'a> ShapeMesh<'a> {
+	fn add_vertex(&mut self, point: Point) -> u32 {
+		let color = self.paint.color_at(point);
+		self.vertices.push(Vertex {
+			pos: [point.x, point.y],
+			uv: [0.0, 0.0],
+			color: [color.red, color.green, color.blue, color.alpha],
+		});
+		(self.vertices.len() - 1) as u32
+	}
+
+	/// Adds a triangle, splitting it into a grid of smaller
+	/// triangles if it's large and has a gradient.
+	fn add_triangle(&mut self, [a, b, c]: [Point; 3]) {
+		let divisions = match self.paint {
+			Paint::Solid(_) => 1,
+			_ => {
+				let longest_edge = distance(a, b).max(distance(b, c)).max(distance(c, a));
+				division_count(longest_edge)
+			}
+		};
+		// the vertex at step (i, j) is i steps from a towards b
+		// and j steps from a towards c
+		let mut grid = vec![];
+		for i in 0..=divisions {
+			let mut row = vec![];
+			for j in 0..=(divisions - i) {
+				let u = i as f32 / divisions as f32;
+				let v = j as f32 / divisions as f32;
+				row.push(self.add_vertex(Point::new(
+					a.x + (b.x - a.x) * u + (c.x - a.x) * v,
+					a.y + (b.y - a.y) * u + (c.y - a.y) * v,
+				)));
+			}
+			grid.push(row);
+		}
+		for i in 0..divisions {
+			for j in 0..(divisions - i) {
+				self.indices
+					.extend_from_slice(&[grid[i][j], grid[i + 1][j], grid[i][j + 1]]);
+				if j + 1 < divisions - i {
+					self.indices.extend_from_slice(&[
+						grid[i + 1][j],
+						grid[i + 1][j + 1],
+						grid[i][j + 1],
+					]);
+				}
+			}
+		}
+	}
+}
+
+fn distance(a: Point, b: Point) -> f32 {
+	(b.x - a.x).hypot(b.y - a.y)
+}
+
+fn division_count(length: f32) -> usize {
+	((length / MAX_GRADIENT_EDGE_LENGTH).ceil() as usize).clamp(1, MAX_GRADIENT_DIVISIONS)
+}
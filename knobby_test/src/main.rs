@@ -1,11 +1,13 @@
 use ggez::graphics;
 use knobby::{
 	behavior::Behavior,
-	canvas::{Alignment, ArcKind, Canvas, Color, DrawMode, Style, TextStyle},
+	canvas::{Alignment, ArcKind, Canvas, Color, DrawMode, Paint, Stroke, Style, TextStyle},
 	control::{Control, ControlSettings},
 	event::Event,
 	geometry::{Point, Rectangle},
 	gui::{Controls, EventQueue, Resources},
+	meter::Meters,
+	parameter::Parameters,
 };
 use knobby_ggez_backend::GgezBackend;
 use std::{collections::HashMap, f32::consts::PI};
@@ -26,12 +28,19 @@ impl Outline {
 }
 
 impl Behavior<CustomEvent> for Outline {
-	fn draw(&self, control: &Control, _resources: &Resources, canvas: &mut Canvas) {
+	fn draw(
+		&self,
+		control: &Control,
+		_resources: &Resources,
+		_parameters: &Parameters,
+		_meters: &Meters,
+		canvas: &mut Canvas,
+	) {
 		canvas.draw_rectangle(
 			control.rectangle,
 			Style {
-				mode: DrawMode::Stroke(2.0),
-				color: self.color,
+				mode: DrawMode::Stroke(Stroke::new(2.0)),
+				paint: Paint::Solid(self.color),
 			},
 		);
 	}
@@ -54,7 +63,14 @@ impl Text {
 }
 
 impl Behavior<CustomEvent> for Text {
-	fn draw(&self, control: &Control, _resources: &Resources, canvas: &mut Canvas) {
+	fn draw(
+		&self,
+		control: &Control,
+		_resources: &Resources,
+		_parameters: &Parameters,
+		_meters: &Meters,
+		canvas: &mut Canvas,
+	) {
 		canvas.draw_text(
 			self.text.clone(),
 			Point::new(control.rectangle.x, control.rectangle.y),
@@ -87,6 +103,9 @@ impl MainState {
 			ControlSettings {
 				rectangle: rect_1,
 				height: 0,
+				parent: None,
+				visible: true,
+				focusable: false,
 			},
 			vec![
 				Box::new(Outline::new(Color::new(1.0, 1.0, 1.0, 1.0))),
@@ -98,8 +117,8 @@ impl MainState {
 }
 
 impl ggez::event::EventHandler for MainState {
-	fn update(&mut self, _ctx: &mut ggez::Context) -> ggez::GameResult {
-		Ok(())
+	fn update(&mut self, ctx: &mut ggez::Context) -> ggez::GameResult {
+		self.backend.update(ctx)
 	}
 
 	fn mouse_motion_event(&mut self, ctx: &mut ggez::Context, x: f32, y: f32, dx: f32, dy: f32) {
@@ -126,13 +145,26 @@ impl ggez::event::EventHandler for MainState {
 		self.backend.mouse_button_up_event(ctx, button, x, y);
 	}
 
+	fn mouse_wheel_event(&mut self, ctx: &mut ggez::Context, x: f32, y: f32) {
+		self.backend.mouse_wheel_event(ctx, x, y);
+	}
+
+	fn resize_event(&mut self, ctx: &mut ggez::Context, width: f32, height: f32) {
+		self.backend.resize_event(ctx, width, height);
+	}
+
+	fn focus_event(&mut self, ctx: &mut ggez::Context, gained: bool) {
+		self.backend.focus_event(ctx, gained);
+	}
+
 	fn key_down_event(
 		&mut self,
 		ctx: &mut ggez::Context,
 		keycode: ggez::event::KeyCode,
-		_keymods: ggez::event::KeyMods,
-		_repeat: bool,
+		keymods: ggez::event::KeyMods,
+		repeat: bool,
 	) {
+		self.backend.key_down_event(ctx, keycode, keymods, repeat);
 		match keycode {
 			ggez::event::KeyCode::Space => {
 				self.backend
@@ -143,7 +175,26 @@ impl ggez::event::EventHandler for MainState {
 		}
 	}
 
+	fn key_up_event(
+		&mut self,
+		ctx: &mut ggez::Context,
+		keycode: ggez::event::KeyCode,
+		keymods: ggez::event::KeyMods,
+	) {
+		self.backend.key_up_event(ctx, keycode, keymods);
+	}
+
+	fn text_input_event(&mut self, ctx: &mut ggez::Context, character: char) {
+		self.backend.text_input_event(ctx, character);
+	}
+
 	fn draw(&mut self, ctx: &mut ggez::Context) -> ggez::GameResult {
+		// the last frame is still on screen, so there's no need
+		// to draw it again
+		if !self.backend.gui.needs_redraw() {
+			ggez::timer::yield_now();
+			return Ok(());
+		}
 		graphics::clear(ctx, graphics::BLACK);
 		self.backend.draw(ctx)?;
 		graphics::present(ctx)?;